@@ -1,3 +1,5 @@
+use std::io;
+
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -5,3 +7,66 @@ pub enum Errors {
     #[error("This is a testing error for the obelisk engine.")]
     TestError,
 }
+
+/// Errors that can occur while building a `ShaderProgram`.
+#[derive(Error, Debug)]
+pub enum ShaderError {
+    #[error("Failed to open {path}: {source}")]
+    FileOpen { path: String, source: io::Error },
+    #[error("Failed to read {path}: {source}")]
+    FileRead { path: String, source: io::Error },
+    #[error("Failed to write {path}: {source}")]
+    FileWrite { path: String, source: io::Error },
+    #[error("{stage} shader source contains a NUL byte at position {position}")]
+    InvalidSource { stage: String, position: usize },
+    #[error("{log}")]
+    Compile { stage: String, log: String },
+    #[error("{0}")]
+    Link(String),
+    #[error("Shader program was not created from files and cannot be reloaded")]
+    NotReloadable,
+    #[error("{0} is not a valid program binary")]
+    InvalidBinary(String),
+    #[error("The driver did not provide a binary for this program")]
+    BinaryUnavailable,
+    #[error("Invalid include directive: {0}")]
+    InvalidInclude(String),
+    #[error("{0} is included recursively")]
+    IncludeCycle(String),
+    #[error("{0} is not supported by this context")]
+    Unsupported(&'static str),
+}
+
+/// Errors that can occur while loading a texture.
+#[derive(Error, Debug)]
+pub enum TextureError {
+    #[cfg(feature = "image")]
+    #[error("Failed to load image {path}: {source}")]
+    Image {
+        path: String,
+        source: image::ImageError,
+    },
+    #[cfg(feature = "bindless")]
+    #[error("Bindless textures are not supported by this context")]
+    BindlessUnsupported,
+}
+
+/// Errors that can occur while loading a `Mesh`.
+#[derive(Error, Debug)]
+pub enum MeshError {
+    #[cfg(feature = "tobj")]
+    #[error("Failed to load model {path}: {source}")]
+    Obj {
+        path: String,
+        source: tobj::LoadError,
+    },
+}
+
+/// Errors that can occur while creating a `TextRenderer`.
+#[derive(Error, Debug)]
+pub enum FontError {
+    #[error("Failed to parse font: {0}")]
+    Parse(&'static str),
+    #[error(transparent)]
+    Shader(#[from] ShaderError),
+}
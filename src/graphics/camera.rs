@@ -0,0 +1,62 @@
+use cgmath::*;
+
+/// # Camera
+/// Builds the view and projection matrices for a scene. There is no input
+/// handling, only the matrix math.
+///
+/// ## Example
+/// ```ignore
+/// let mut camera = Camera::perspective(Deg(60.0), 16.0 / 9.0, 0.1, 100.0);
+/// camera.look_at(Point3::new(0.0, 2.0, 5.0), Point3::origin(), Vector3::unit_y());
+///
+/// program.set_matrix4fv_uniform("view_projection", &camera.view_projection());
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct Camera {
+    projection: Matrix4<f32>,
+    view: Matrix4<f32>,
+}
+
+impl Camera {
+    /// Create a camera with a perspective projection, looking down -Z from the origin.
+    pub fn perspective<A: Into<Rad<f32>>>(fov: A, aspect: f32, near: f32, far: f32) -> Camera {
+        Camera {
+            projection: cgmath::perspective(fov, aspect, near, far),
+            view: Matrix4::identity(),
+        }
+    }
+
+    /// Create a camera with an orthographic projection, e.g. for 2D or HUD
+    /// rendering. `Camera::orthographic(0.0, width, height, 0.0, -1.0, 1.0)` maps
+    /// units to pixels with the origin in the top-left corner.
+    pub fn orthographic(
+        left: f32,
+        right: f32,
+        bottom: f32,
+        top: f32,
+        near: f32,
+        far: f32,
+    ) -> Camera {
+        Camera {
+            projection: cgmath::ortho(left, right, bottom, top, near, far),
+            view: Matrix4::identity(),
+        }
+    }
+
+    /// Point the camera from `eye` towards `target`.
+    pub fn look_at(&mut self, eye: Point3<f32>, target: Point3<f32>, up: Vector3<f32>) {
+        self.view = Matrix4::look_at_rh(eye, target, up);
+    }
+
+    pub fn projection(&self) -> Matrix4<f32> {
+        self.projection
+    }
+
+    pub fn view(&self) -> Matrix4<f32> {
+        self.view
+    }
+
+    pub fn view_projection(&self) -> Matrix4<f32> {
+        self.projection * self.view
+    }
+}
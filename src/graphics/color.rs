@@ -0,0 +1,54 @@
+/// # Color
+/// An RGBA color with components normalized to `0.0..=1.0`.
+///
+/// ## Example
+/// ```ignore
+/// let orange = Color::from_hex("#ff8800").unwrap();
+/// let translucent_red = Color::rgba(1.0, 0.0, 0.0, 0.5);
+///
+/// program.set_uniform_color("tint", &orange);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Color {
+    pub r: f32,
+    pub g: f32,
+    pub b: f32,
+    pub a: f32,
+}
+
+impl Color {
+    pub const WHITE: Color = Color::rgb(1.0, 1.0, 1.0);
+    pub const BLACK: Color = Color::rgb(0.0, 0.0, 0.0);
+
+    /// An opaque color.
+    pub const fn rgb(r: f32, g: f32, b: f32) -> Color {
+        Color { r, g, b, a: 1.0 }
+    }
+
+    pub const fn rgba(r: f32, g: f32, b: f32, a: f32) -> Color {
+        Color { r, g, b, a }
+    }
+
+    /// Parse a `#rrggbb` or `#rrggbbaa` hex string. The `#` is optional.
+    pub fn from_hex(hex: &str) -> Option<Color> {
+        let hex = hex.strip_prefix('#').unwrap_or(hex);
+        // `from_str_radix` accepts a leading `+`, so check every digit first.
+        if !matches!(hex.len(), 6 | 8) || !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return None;
+        }
+
+        let channel = |index: usize| {
+            u8::from_str_radix(&hex[index * 2..index * 2 + 2], 16)
+                .ok()
+                .map(|value| value as f32 / 255.0)
+        };
+
+        let alpha = if hex.len() == 8 { channel(3)? } else { 1.0 };
+
+        Some(Color::rgba(channel(0)?, channel(1)?, channel(2)?, alpha))
+    }
+
+    pub fn to_array(self) -> [f32; 4] {
+        [self.r, self.g, self.b, self.a]
+    }
+}
@@ -1,21 +1,74 @@
+//! Thin wrappers around OpenGL objects.
+//!
+//! Each wrapper owns its GL object and deletes it when dropped. They are
+//! deliberately neither `Clone` nor `Copy`, since a copied handle would be
+//! deleted twice. `Vao`, `BufferObject`, `Texture2D`, `Cubemap`, `Sampler`,
+//! `Renderbuffer`, `Framebuffer` and `ShaderProgram` have `into_raw`/`from_raw`
+//! to hand their object to code outside this crate without that double delete.
+
+use std::cell::{Cell, RefCell};
 use std::collections::HashMap;
-use std::ffi::CString;
+use std::collections::HashSet;
+use std::ffi::{CStr, CString};
 use std::fs::File;
+use std::marker::PhantomData;
 use std::mem;
+use std::ops::Deref;
+use std::panic::{self, AssertUnwindSafe};
 
 use std::io::Read;
 
 use std::os::raw::*;
+use std::path::{Path, PathBuf};
 use std::ptr;
+use std::slice;
 
 use gl::types::*;
 
 use cgmath::*;
 
-/// # Vertex Array Object
+use crate::custom_errors::ShaderError;
+#[cfg(any(feature = "image", feature = "bindless"))]
+use crate::custom_errors::TextureError;
+use crate::graphics::color::Color;
+
+/// GL objects that are bound to a target before use.
+pub trait Bind {
+    fn bind(&self);
+    fn unbind(&self);
+}
+
+/// # Bind Guard
+/// Binds an object on creation and unbinds it when dropped.
 ///
 /// ## Example
+/// ```ignore
+/// {
+///     let _bound = vao.bind_scoped();
+///     draw_arrays(gl::TRIANGLES, 0, 3);
+/// } // vao is unbound here
 /// ```
+pub struct BindGuard<'a, T: Bind> {
+    object: &'a T,
+}
+
+impl<'a, T: Bind> BindGuard<'a, T> {
+    pub fn new(object: &'a T) -> BindGuard<'a, T> {
+        object.bind();
+        BindGuard { object }
+    }
+}
+
+impl<T: Bind> Drop for BindGuard<'_, T> {
+    fn drop(&mut self) {
+        self.object.unbind();
+    }
+}
+
+/// # Vertex Array Object
+///
+/// ## Example
+/// ```ignore
 /// let vao = Vao::new();
 /// vao.bind();
 /// ```
@@ -27,31 +80,278 @@ impl Vao {
     pub fn new() -> Vao {
         let mut id = 0;
         unsafe {
-            gl::GenVertexArrays(1, &mut id);
+            // Created names exist right away, so they can be labelled before
+            // their first bind.
+            if supports_dsa() {
+                gl::CreateVertexArrays(1, &mut id);
+            } else {
+                gl::GenVertexArrays(1, &mut id);
+            }
         }
 
         Vao { id }
     }
 
+    /// Release ownership of the GL object without deleting it.
+    pub fn into_raw(mut self) -> GLuint {
+        // The new owner may change or delete it without going through the cache.
+        forget_enabled_attributes(self.id);
+        mem::replace(&mut self.id, 0)
+    }
+
+    /// Take ownership of an existing vertex array object.
+    ///
+    /// # Safety
+    /// `id` must be a vertex array name that nothing else will delete.
+    pub unsafe fn from_raw(id: GLuint) -> Vao {
+        // Anything cached for this name may belong to an earlier, deleted VAO.
+        forget_enabled_attributes(id);
+        Vao { id }
+    }
+
     pub fn bind(&self) {
         unsafe {
             gl::BindVertexArray(self.id);
         }
+        STATE_CACHE.with(|cache| cache.borrow_mut().bound_vao = Some(self.id));
     }
 
     pub fn unbind(&self) {
         unsafe {
             gl::BindVertexArray(0);
         }
+        STATE_CACHE.with(|cache| cache.borrow_mut().bound_vao = Some(0));
+    }
+
+    /// Bind until the returned guard is dropped.
+    pub fn bind_scoped(&self) -> BindGuard<'_, Vao> {
+        BindGuard::new(self)
+    }
+
+    /// Name the vertex array in debugging tools such as RenderDoc. Does nothing
+    /// without `KHR_debug`.
+    ///
+    /// Without direct state access the VAO has to have been bound once, since GL
+    /// raises `GL_INVALID_VALUE` for names that don't exist yet.
+    pub fn set_label(&self, label: &str) {
+        set_object_label(gl::VERTEX_ARRAY, self.id, label);
+    }
+
+    /// Describe and enable attribute `index` without tying it to a buffer, for
+    /// use with `bind_vertex_buffer`. `relative_offset` is the attribute's byte
+    /// offset within a vertex. The VAO has to be bound.
+    ///
+    /// ## Example
+    /// ```ignore
+    /// vao.bind();
+    /// vao.set_attrib_format(0, 3, gl::FLOAT, false, 0);
+    /// vao.set_attrib_format(1, 2, gl::FLOAT, false, 12);
+    /// vao.set_attrib_binding(0, 0);
+    /// vao.set_attrib_binding(1, 0);
+    ///
+    /// for mesh in &meshes {
+    ///     vao.bind_vertex_buffer(0, mesh.vbo(), 0, 20);
+    ///     draw_arrays(gl::TRIANGLES, 0, mesh.vertex_count());
+    /// }
+    /// ```
+    pub fn set_attrib_format(
+        &self,
+        index: u32,
+        size: i32,
+        r#type: GLenum,
+        normalized: bool,
+        relative_offset: u32,
+    ) {
+        unsafe {
+            gl::VertexAttribFormat(
+                index,
+                size,
+                r#type,
+                normalized as GLboolean,
+                relative_offset,
+            );
+        }
+        enable_vertex_attrib_array(index);
+    }
+
+    /// Like `set_attrib_format` for integer attributes read as `int`/`uint` in
+    /// the shader.
+    pub fn set_attrib_integer_format(
+        &self,
+        index: u32,
+        size: i32,
+        r#type: GLenum,
+        relative_offset: u32,
+    ) {
+        unsafe {
+            gl::VertexAttribIFormat(index, size, r#type, relative_offset);
+        }
+        enable_vertex_attrib_array(index);
+    }
+
+    /// Read attribute `index` from the buffer bound to `binding_index`.
+    pub fn set_attrib_binding(&self, index: u32, binding_index: u32) {
+        unsafe {
+            gl::VertexAttribBinding(index, binding_index);
+        }
+    }
+
+    /// Attach `buffer` to `binding_index`, starting `offset` bytes in with
+    /// `stride` bytes per vertex. Swapping buffers keeps the attribute formats.
+    pub fn bind_vertex_buffer(
+        &self,
+        binding_index: u32,
+        buffer: &BufferObject,
+        offset: isize,
+        stride: i32,
+    ) {
+        unsafe {
+            gl::BindVertexBuffer(binding_index, buffer.id, offset, stride);
+        }
+        check_gl_error("Vao::bind_vertex_buffer");
+    }
+}
+
+impl Default for Vao {
+    fn default() -> Vao {
+        Vao::new()
+    }
+}
+
+/// Deletes the vertex array object.
+///
+/// Must be dropped on the thread that owns the GL context.
+impl Drop for Vao {
+    fn drop(&mut self) {
+        if self.id != 0 {
+            unsafe {
+                gl::DeleteVertexArrays(1, &self.id);
+            }
+            // The name may be reused, and deleting a bound VAO binds 0.
+            forget_enabled_attributes(self.id);
+            STATE_CACHE.with(|cache| {
+                let mut cache = cache.borrow_mut();
+                if cache.bound_vao == Some(self.id) {
+                    cache.bound_vao = Some(0);
+                }
+            });
+        }
+    }
+}
+
+impl Bind for Vao {
+    fn bind(&self) {
+        Vao::bind(self);
+    }
+
+    fn unbind(&self) {
+        Vao::unbind(self);
+    }
+}
+
+/// The binding target of a `BufferObject`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BufferTarget {
+    Array,
+    ElementArray,
+    Uniform,
+    ShaderStorage,
+    TransformFeedback,
+    CopyRead,
+    CopyWrite,
+    PixelPack,
+    PixelUnpack,
+    DrawIndirect,
+    DispatchIndirect,
+    Texture,
+    AtomicCounter,
+    Query,
+}
+
+impl From<BufferTarget> for GLenum {
+    fn from(target: BufferTarget) -> GLenum {
+        match target {
+            BufferTarget::Array => gl::ARRAY_BUFFER,
+            BufferTarget::ElementArray => gl::ELEMENT_ARRAY_BUFFER,
+            BufferTarget::Uniform => gl::UNIFORM_BUFFER,
+            BufferTarget::ShaderStorage => gl::SHADER_STORAGE_BUFFER,
+            BufferTarget::TransformFeedback => gl::TRANSFORM_FEEDBACK_BUFFER,
+            BufferTarget::CopyRead => gl::COPY_READ_BUFFER,
+            BufferTarget::CopyWrite => gl::COPY_WRITE_BUFFER,
+            BufferTarget::PixelPack => gl::PIXEL_PACK_BUFFER,
+            BufferTarget::PixelUnpack => gl::PIXEL_UNPACK_BUFFER,
+            BufferTarget::DrawIndirect => gl::DRAW_INDIRECT_BUFFER,
+            BufferTarget::DispatchIndirect => gl::DISPATCH_INDIRECT_BUFFER,
+            BufferTarget::Texture => gl::TEXTURE_BUFFER,
+            BufferTarget::AtomicCounter => gl::ATOMIC_COUNTER_BUFFER,
+            BufferTarget::Query => gl::QUERY_BUFFER,
+        }
+    }
+}
+
+/// How often a buffer's contents are expected to change (`Static`: once,
+/// `Dynamic`: repeatedly, `Stream`: every use) and who accesses them (`Draw`:
+/// written by the CPU, `Read`: read back by the CPU, `Copy`: GPU only). This is
+/// only a hint for the driver.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BufferUsage {
+    StreamDraw,
+    StreamRead,
+    StreamCopy,
+    StaticDraw,
+    StaticRead,
+    StaticCopy,
+    DynamicDraw,
+    DynamicRead,
+    DynamicCopy,
+}
+
+impl From<BufferUsage> for GLenum {
+    fn from(usage: BufferUsage) -> GLenum {
+        match usage {
+            BufferUsage::StreamDraw => gl::STREAM_DRAW,
+            BufferUsage::StreamRead => gl::STREAM_READ,
+            BufferUsage::StreamCopy => gl::STREAM_COPY,
+            BufferUsage::StaticDraw => gl::STATIC_DRAW,
+            BufferUsage::StaticRead => gl::STATIC_READ,
+            BufferUsage::StaticCopy => gl::STATIC_COPY,
+            BufferUsage::DynamicDraw => gl::DYNAMIC_DRAW,
+            BufferUsage::DynamicRead => gl::DYNAMIC_READ,
+            BufferUsage::DynamicCopy => gl::DYNAMIC_COPY,
+        }
     }
 }
 
+/// Numeric types for which every bit pattern is a valid value and that contain
+/// no padding, so they can be built from raw GPU memory or compared byte by
+/// byte. Implemented for `u8`, `u16`, `u32`, `i32`, `f32` and arrays of them,
+/// and sealed so it can't be implemented for other types.
+pub trait PlainData: Copy + private::Sealed {}
+
+mod private {
+    pub trait Sealed {}
+}
+
+impl private::Sealed for u8 {}
+impl private::Sealed for u16 {}
+impl private::Sealed for u32 {}
+impl private::Sealed for i32 {}
+impl private::Sealed for f32 {}
+impl<T: PlainData, const N: usize> private::Sealed for [T; N] {}
+
+impl PlainData for u8 {}
+impl PlainData for u16 {}
+impl PlainData for u32 {}
+impl PlainData for i32 {}
+impl PlainData for f32 {}
+impl<T: PlainData, const N: usize> PlainData for [T; N] {}
+
 /// # Buffer Object
 /// An object for storing data
 ///
 /// ## Example
-/// ```
-/// let vbo = BufferObject::new(gl::ARRAY_BUFFER, gl::STATIC_DRAW);
+/// ```ignore
+/// let vbo = BufferObject::new(BufferTarget::Array, BufferUsage::StaticDraw);
 /// vbo.bind();
 ///
 /// vbo.store_f32_data(&float32_array);
@@ -60,15 +360,91 @@ pub struct BufferObject {
     id: gl::types::GLuint,
     r#type: gl::types::GLenum,
     usage: gl::types::GLenum,
+    byte_len: Cell<usize>,
+    element_size: Cell<usize>,
 }
 
 impl BufferObject {
-    pub fn new(r#type: gl::types::GLenum, usage: gl::types::GLenum) -> BufferObject {
+    pub fn new(target: BufferTarget, usage: BufferUsage) -> BufferObject {
+        BufferObject::new_raw(target.into(), usage.into())
+    }
+
+    /// Like `new`, but with raw GL enums for targets or usages not covered by
+    /// `BufferTarget` and `BufferUsage`.
+    pub fn new_raw(r#type: gl::types::GLenum, usage: gl::types::GLenum) -> BufferObject {
         let mut id = 0;
         unsafe {
-            gl::GenBuffers(1, &mut id);
+            // Created buffers exist right away, so `upload_data` can use them
+            // before they were ever bound.
+            if supports_dsa() {
+                gl::CreateBuffers(1, &mut id);
+            } else {
+                gl::GenBuffers(1, &mut id);
+            }
+        }
+        BufferObject {
+            id,
+            r#type,
+            usage,
+            byte_len: Cell::new(0),
+            element_size: Cell::new(1),
+        }
+    }
+
+    /// A `GL_ARRAY_BUFFER` with `GL_STATIC_DRAW` usage, for vertex data.
+    pub fn array_buffer() -> BufferObject {
+        BufferObject::new(BufferTarget::Array, BufferUsage::StaticDraw)
+    }
+
+    /// A `GL_ELEMENT_ARRAY_BUFFER` with `GL_STATIC_DRAW` usage, for indices.
+    pub fn element_buffer() -> BufferObject {
+        BufferObject::new(BufferTarget::ElementArray, BufferUsage::StaticDraw)
+    }
+
+    /// Release ownership of the GL object without deleting it.
+    pub fn into_raw(mut self) -> GLuint {
+        mem::replace(&mut self.id, 0)
+    }
+
+    /// Take ownership of an existing buffer object. Its size reads as 0 until
+    /// data is stored through the wrapper.
+    ///
+    /// # Safety
+    /// `id` must be a buffer name that nothing else will delete.
+    pub unsafe fn from_raw(
+        id: GLuint,
+        r#type: gl::types::GLenum,
+        usage: gl::types::GLenum,
+    ) -> BufferObject {
+        BufferObject {
+            id,
+            r#type,
+            usage,
+            byte_len: Cell::new(0),
+            element_size: Cell::new(1),
         }
-        BufferObject { id, r#type, usage }
+    }
+
+    /// Size of the buffer's storage in bytes, as of the last store or
+    /// allocation.
+    pub fn byte_len(&self) -> usize {
+        self.byte_len.get()
+    }
+
+    /// Number of elements of the type last passed to `store_data` or
+    /// `upload_data`, e.g. the vertex count for `draw_arrays`. After `allocate`
+    /// this counts bytes.
+    pub fn len(&self) -> usize {
+        self.byte_len.get() / self.element_size.get()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.byte_len.get() == 0
+    }
+
+    fn set_size<T>(&self, data: &[T]) {
+        self.byte_len.set(mem::size_of_val(data));
+        self.element_size.set(mem::size_of::<T>().max(1));
     }
 
     pub fn bind(&self) {
@@ -83,164 +459,4556 @@ impl BufferObject {
         }
     }
 
+    /// Bind until the returned guard is dropped.
+    pub fn bind_scoped(&self) -> BindGuard<'_, BufferObject> {
+        BindGuard::new(self)
+    }
+
+    /// Name the buffer in debugging tools such as RenderDoc. Does nothing
+    /// without `KHR_debug`.
+    ///
+    /// Without direct state access the buffer has to have been bound once, since
+    /// GL raises `GL_INVALID_VALUE` for names that don't exist yet.
+    pub fn set_label(&self, label: &str) {
+        set_object_label(gl::BUFFER, self.id, label);
+    }
+
     pub fn store_f32_data(&self, data: &[f32]) {
+        self.store_data(data);
+    }
+
+    pub fn store_i32_data(&self, data: &[i32]) {
+        self.store_data(data);
+    }
+
+    pub fn store_u32_data(&self, data: &[u32]) {
+        self.store_data(data);
+    }
+
+    pub fn store_u16_data(&self, data: &[u16]) {
+        self.store_data(data);
+    }
+
+    pub fn store_u8_data(&self, data: &[u8]) {
+        self.store_data(data);
+    }
+
+    /// Allocate `size_bytes` of uninitialized storage, to be filled later with
+    /// the `update_*_data` functions.
+    pub fn allocate(&self, size_bytes: usize) {
         unsafe {
             gl::BufferData(
                 self.r#type,
-                (data.len() * mem::size_of::<gl::types::GLfloat>()) as gl::types::GLsizeiptr,
-                &data[0] as *const f32 as *const c_void,
+                size_bytes as gl::types::GLsizeiptr,
+                ptr::null(),
                 self.usage,
             );
         }
+        check_gl_error("BufferObject::allocate");
+        self.byte_len.set(size_bytes);
+        self.element_size.set(1);
     }
 
-    pub fn store_i32_data(&self, data: &[i32]) {
+    /// Overwrite part of the buffer starting at byte `offset` without
+    /// reallocating its storage.
+    pub fn update_f32_data(&self, offset: usize, data: &[f32]) {
+        self.buffer_sub_data(offset, data);
+        check_gl_error("BufferObject::update_f32_data");
+    }
+
+    fn buffer_sub_data<T>(&self, offset: usize, data: &[T]) {
+        unsafe {
+            gl::BufferSubData(
+                self.r#type,
+                offset as gl::types::GLintptr,
+                mem::size_of_val(data) as gl::types::GLsizeiptr,
+                data.as_ptr() as *const c_void,
+            );
+        }
+    }
+
+    /// Allocate immutable storage of `size_bytes` that can stay mapped for
+    /// writing while the GPU uses it. Requires GL 4.4.
+    pub fn allocate_persistent(&self, size_bytes: usize) {
+        unsafe {
+            gl::BufferStorage(
+                self.r#type,
+                size_bytes as gl::types::GLsizeiptr,
+                ptr::null(),
+                PERSISTENT_MAP_FLAGS,
+            );
+        }
+        check_gl_error("BufferObject::allocate_persistent");
+        self.byte_len.set(size_bytes);
+        self.element_size.set(1);
+    }
+
+    /// Map the whole buffer persistently. The buffer must have been created with
+    /// `allocate_persistent` and be bound to its target, and the pointer stays
+    /// valid until `unmap`.
+    pub fn map_persistent(&self) -> *mut c_void {
+        let mut size = 0;
+        let pointer = unsafe {
+            gl::GetBufferParameteri64v(self.r#type, gl::BUFFER_SIZE, &mut size);
+            gl::MapBufferRange(
+                self.r#type,
+                0,
+                size as gl::types::GLsizeiptr,
+                PERSISTENT_MAP_FLAGS,
+            )
+        };
+        check_gl_error("BufferObject::map_persistent");
+
+        pointer
+    }
+
+    /// Map the whole buffer persistently as a slice of `T`. The buffer has to
+    /// be bound to its target, since its size is read through the binding.
+    ///
+    /// Panics if `T` is zero-sized or the buffer size is not a multiple of
+    /// `size_of::<T>()`.
+    ///
+    /// # Safety
+    /// The slice must not be used after `unmap`, and the caller has to make sure
+    /// the GPU is done with a region (e.g. with a fence) before overwriting it.
+    pub unsafe fn map_persistent_slice<T>(&mut self) -> &mut [T] {
+        let element_size = mem::size_of::<T>();
+        assert!(
+            element_size != 0,
+            "Cannot map a buffer as zero-sized elements"
+        );
+
+        let mut size = 0;
+        gl::GetBufferParameteri64v(self.r#type, gl::BUFFER_SIZE, &mut size);
+        assert!(
+            (size as usize).is_multiple_of(element_size),
+            "Buffer size {} is not a multiple of the element size {}",
+            size,
+            element_size
+        );
+
+        let pointer = self.map_persistent() as *mut T;
+        if pointer.is_null() {
+            return &mut [];
+        }
+
+        slice::from_raw_parts_mut(pointer, size as usize / element_size)
+    }
+
+    pub fn unmap(&self) {
+        unsafe {
+            gl::UnmapBuffer(self.r#type);
+        }
+    }
+
+    /// Copy the first `count` elements of the buffer back to the CPU. Binds the
+    /// buffer. This stalls until the GPU has finished writing to it, so use it
+    /// for debugging and tests, not every frame.
+    ///
+    /// ## Example
+    /// ```ignore
+    /// simulation.dispatch(particle_count / 64, 1, 1);
+    /// memory_barrier(gl::BUFFER_UPDATE_BARRIER_BIT);
+    ///
+    /// let positions: Vec<[f32; 4]> = particles.read_to_vec(particle_count as usize);
+    /// ```
+    pub fn read_to_vec<T: PlainData>(&self, count: usize) -> Vec<T> {
+        self.bind();
+
+        let mut size = 0;
+        unsafe {
+            gl::GetBufferParameteri64v(self.r#type, gl::BUFFER_SIZE, &mut size);
+        }
+        let count = count.min(size.max(0) as usize / mem::size_of::<T>().max(1));
+        if count == 0 {
+            return Vec::new();
+        }
+
+        let mut data = Vec::with_capacity(count);
+        unsafe {
+            let pointer = gl::MapBufferRange(
+                self.r#type,
+                0,
+                (count * mem::size_of::<T>()) as gl::types::GLsizeiptr,
+                gl::MAP_READ_BIT,
+            ) as *const T;
+            if !pointer.is_null() {
+                data.extend_from_slice(slice::from_raw_parts(pointer, count));
+                gl::UnmapBuffer(self.r#type);
+            }
+        }
+        check_gl_error("BufferObject::read_to_vec");
+
+        data
+    }
+
+    /// Upload a slice of any plain-old-data type, replacing the buffer's storage.
+    ///
+    /// The bytes of `data` are copied as they are in memory, so vertex structs
+    /// need `#[repr(C)]` to get a layout that matches the `VertexLayout`. Empty
+    /// slices allocate an empty buffer.
+    ///
+    /// ## Example
+    /// ```ignore
+    /// #[repr(C)]
+    /// #[derive(Clone, Copy)]
+    /// struct Vertex {
+    ///     position: [f32; 3],
+    ///     uv: [f32; 2],
+    /// }
+    ///
+    /// vbo.store_data(&vertices);
+    /// ```
+    pub fn store_data<T: Copy>(&self, data: &[T]) {
         unsafe {
             gl::BufferData(
                 self.r#type,
-                (data.len() * mem::size_of::<gl::types::GLfloat>()) as gl::types::GLsizeiptr,
-                &data[0] as *const i32 as *const c_void,
+                mem::size_of_val(data) as gl::types::GLsizeiptr,
+                data.as_ptr() as *const c_void,
                 self.usage,
             );
         }
+        check_gl_error("BufferObject::store_data");
+        self.set_size(data);
+    }
+
+    /// Like `store_data`, but the buffer doesn't have to be bound. Uses direct
+    /// state access when the context supports it and binds the buffer otherwise.
+    pub fn upload_data<T: Copy>(&self, data: &[T]) {
+        if supports_dsa() {
+            unsafe {
+                gl::NamedBufferData(
+                    self.id,
+                    mem::size_of_val(data) as gl::types::GLsizeiptr,
+                    data.as_ptr() as *const c_void,
+                    self.usage,
+                );
+            }
+            check_gl_error("BufferObject::upload_data");
+            self.set_size(data);
+        } else {
+            self.bind();
+            self.store_data(data);
+        }
     }
 }
 
-/// # Vertex Attribute
-/// Discribes vertex data
+const PERSISTENT_MAP_FLAGS: GLbitfield =
+    gl::MAP_WRITE_BIT | gl::MAP_PERSISTENT_BIT | gl::MAP_COHERENT_BIT;
+
+/// Deletes the buffer object.
+///
+/// Must be dropped on the thread that owns the GL context.
+impl Drop for BufferObject {
+    fn drop(&mut self) {
+        if self.id != 0 {
+            unsafe {
+                gl::DeleteBuffers(1, &self.id);
+            }
+        }
+    }
+}
+
+impl Bind for BufferObject {
+    fn bind(&self) {
+        BufferObject::bind(self);
+    }
+
+    fn unbind(&self) {
+        BufferObject::unbind(self);
+    }
+}
+
+/// # Uniform Buffer
+/// A `GL_UNIFORM_BUFFER` for sharing uniform data such as camera matrices
+/// between programs. It dereferences to `BufferObject` for uploading data.
 ///
 /// ## Example
+/// ```ignore
+/// let camera_ubo = UniformBuffer::new(BufferUsage::DynamicDraw);
+/// camera_ubo.bind();
+/// camera_ubo.store_f32_data(&camera_data);
+/// camera_ubo.bind_to_point(0);
+///
+/// program.bind_uniform_block("Camera", 0);
 /// ```
-/// let position_attribute = VertexAttribute::new(0, 3, gl::FLOAT, gl::FALSE, 3 * mem::size_of::<GLfloat>() as GLsizei, ptr::null());
-/// position_attribute.enable()
-/// ```
-pub struct VertexAttribute {
-    index: GLuint,
+pub struct UniformBuffer {
+    buffer: BufferObject,
 }
 
-impl VertexAttribute {
-    pub fn new(
-        index: u32,
-        size: i32,
-        r#type: GLenum,
-        normalized: GLboolean,
-        stride: GLsizei,
-        pointer: *const c_void,
-    ) -> VertexAttribute {
+impl UniformBuffer {
+    pub fn new(usage: BufferUsage) -> UniformBuffer {
+        UniformBuffer {
+            buffer: BufferObject::new(BufferTarget::Uniform, usage),
+        }
+    }
+
+    /// Bind the whole buffer to a uniform block binding point.
+    pub fn bind_to_point(&self, binding_point: u32) {
         unsafe {
-            gl::VertexAttribPointer(index, size, r#type, normalized, stride, pointer);
+            gl::BindBufferBase(gl::UNIFORM_BUFFER, binding_point, self.buffer.id);
         }
+    }
+}
 
-        VertexAttribute { index }
+impl Deref for UniformBuffer {
+    type Target = BufferObject;
+
+    fn deref(&self) -> &BufferObject {
+        &self.buffer
     }
+}
 
-    pub fn enable(&self) {
-        unsafe {
-            gl::EnableVertexAttribArray(self.index);
+/// # Shader Storage Buffer
+/// A `GL_SHADER_STORAGE_BUFFER` that shaders can read and write, sized at
+/// runtime. It dereferences to `BufferObject` for uploading data with
+/// `store_data` and reading it back with `read_to_vec`.
+///
+/// ## Example
+/// ```ignore
+/// let particles = ShaderStorageBuffer::new(BufferUsage::DynamicCopy);
+/// particles.bind();
+/// particles.store_data(&initial_particles);
+/// particles.bind_to_point(0);
+///
+/// simulation.bind_storage_block("Particles", 0);
+/// simulation.dispatch(particle_count / 64, 1, 1);
+/// memory_barrier(gl::BUFFER_UPDATE_BARRIER_BIT);
+///
+/// let result: Vec<[f32; 8]> = particles.read_to_vec(particle_count as usize);
+/// ```
+pub struct ShaderStorageBuffer {
+    buffer: BufferObject,
+}
+
+impl ShaderStorageBuffer {
+    pub fn new(usage: BufferUsage) -> ShaderStorageBuffer {
+        ShaderStorageBuffer {
+            buffer: BufferObject::new(BufferTarget::ShaderStorage, usage),
         }
     }
 
-    pub fn disable(&self) {
+    /// Bind the whole buffer to a shader storage block binding point.
+    pub fn bind_to_point(&self, binding_point: u32) {
         unsafe {
-            gl::DisableVertexAttribArray(self.index);
+            gl::BindBufferBase(gl::SHADER_STORAGE_BUFFER, binding_point, self.buffer.id);
         }
     }
 }
 
-/// # Shader Program
-/// ## Examples
-/// ```
-/// let program = ShaderProgram::new("/path/to/vertexShader.glsl", "/path/to/fragmentShader.glsl");
-/// program.bind();
+impl Deref for ShaderStorageBuffer {
+    type Target = BufferObject;
+
+    fn deref(&self) -> &BufferObject {
+        &self.buffer
+    }
+}
+
+/// # Element Buffer
+/// A `GL_ELEMENT_ARRAY_BUFFER` that remembers the type and number of its
+/// indices, so `draw` always passes the matching type to `glDrawElements`.
+/// Indices can only be uploaded through `store_u16` and `store_u32`, which
+/// keep that type and count in sync.
 ///
-/// program.create_uniform("transform");
+/// ## Example
+/// ```ignore
+/// vao.bind();
+/// let mut indices = ElementBuffer::new(BufferUsage::StaticDraw);
+/// indices.bind();
+/// indices.store_u16(&[0, 1, 2, 2, 3, 0]);
 ///
-/// program.set_matrix4fv_uniform("transform", some_matrix);
+/// indices.draw(gl::TRIANGLES);
 /// ```
-pub struct ShaderProgram {
-    program_handle: u32,
-    uniform_ids: HashMap<String, GLint>,
+pub struct ElementBuffer {
+    buffer: BufferObject,
+    index_type: GLenum,
+    count: i32,
 }
 
-#[allow(temporary_cstring_as_ptr)]
-impl ShaderProgram {
-    pub fn new(vertex_shader_path: &str, fragment_shader_path: &str) -> ShaderProgram {
-        let mut vertex_shader_file = File::open(vertex_shader_path)
-            .unwrap_or_else(|_| panic!("Failed to open {}", vertex_shader_path));
-        let mut fragment_shader_file = File::open(fragment_shader_path)
-            .unwrap_or_else(|_| panic!("Failed to open {}", fragment_shader_path));
-
-        let mut vertex_shader_source = String::new();
-        let mut fragment_shader_source = String::new();
-
-        vertex_shader_file
-            .read_to_string(&mut vertex_shader_source)
-            .expect("Failed to read vertex shader");
+impl ElementBuffer {
+    pub fn new(usage: BufferUsage) -> ElementBuffer {
+        ElementBuffer {
+            buffer: BufferObject::new(BufferTarget::ElementArray, usage),
+            index_type: gl::UNSIGNED_INT,
+            count: 0,
+        }
+    }
 
-        fragment_shader_file
-            .read_to_string(&mut fragment_shader_source)
-            .expect("Failed to read fragment shader");
+    /// Upload 16-bit indices. The buffer has to be bound.
+    pub fn store_u16(&mut self, indices: &[u16]) {
+        self.buffer.store_u16_data(indices);
+        self.index_type = gl::UNSIGNED_SHORT;
+        self.count = indices.len() as i32;
+    }
 
-        unsafe {
-            let vertex_shader = gl::CreateShader(gl::VERTEX_SHADER);
-            let c_str_vert = CString::new(vertex_shader_source.as_bytes()).unwrap();
-            gl::ShaderSource(vertex_shader, 1, &c_str_vert.as_ptr(), ptr::null());
-            gl::CompileShader(vertex_shader);
+    /// Upload 32-bit indices. The buffer has to be bound.
+    pub fn store_u32(&mut self, indices: &[u32]) {
+        self.buffer.store_u32_data(indices);
+        self.index_type = gl::UNSIGNED_INT;
+        self.count = indices.len() as i32;
+    }
 
-            let fragment_shader = gl::CreateShader(gl::FRAGMENT_SHADER);
-            let c_str_frag = CString::new(fragment_shader_source.as_bytes()).unwrap();
-            gl::ShaderSource(fragment_shader, 1, &c_str_frag.as_ptr(), ptr::null());
-            gl::CompileShader(fragment_shader);
+    /// Draw all stored indices. The VAO the buffer is attached to has to be
+    /// bound.
+    pub fn draw(&self, mode: GLenum) {
+        draw_elements(mode, self.count, self.index_type);
+    }
 
-            let program_handle = gl::CreateProgram();
-            gl::AttachShader(program_handle, vertex_shader);
-            gl::AttachShader(program_handle, fragment_shader);
-            gl::LinkProgram(program_handle);
-            gl::DeleteShader(vertex_shader);
-            gl::DeleteShader(fragment_shader);
+    pub fn index_type(&self) -> GLenum {
+        self.index_type
+    }
 
-            ShaderProgram {
-                program_handle,
-                uniform_ids: HashMap::new(),
-            }
-        }
+    pub fn count(&self) -> i32 {
+        self.count
     }
 
     pub fn bind(&self) {
-        unsafe {
-            gl::UseProgram(self.program_handle);
-        }
+        self.buffer.bind();
     }
 
-    pub fn unbind() {
-        unsafe {
-            gl::UseProgram(0);
-        }
+    pub fn unbind(&self) {
+        self.buffer.unbind();
     }
 
-    pub fn create_uniform(&mut self, uniform_name: &str) {
-        let uniform_location = unsafe {
-            gl::GetUniformLocation(
-                self.program_handle,
-                CString::new(uniform_name).unwrap().as_ptr(),
-            )
-        };
-        if uniform_location < 0 {
-            panic!("Cannot locate uniform: {}", uniform_name);
-        } else {
-            self.uniform_ids
-                .insert(uniform_name.to_string(), uniform_location);
-        }
+    /// Name the buffer in debugging tools such as RenderDoc. See
+    /// `BufferObject::set_label`.
+    pub fn set_label(&self, label: &str) {
+        self.buffer.set_label(label);
     }
 
-    pub fn set_matrix4fv_uniform(&self, uniform_name: &str, matrix: &cgmath::Matrix4<f32>) {
+    pub fn id(&self) -> GLuint {
+        self.buffer.id
+    }
+}
+
+/// # Vertex Attribute
+/// Discribes vertex data
+///
+/// ## Example
+/// ```ignore
+/// let position_attribute = VertexAttribute::new(0, 3, gl::FLOAT, gl::FALSE, 3 * mem::size_of::<GLfloat>() as GLsizei, ptr::null());
+/// position_attribute.enable()
+/// ```
+pub struct VertexAttribute {
+    index: GLuint,
+}
+
+// `pointer` is a byte offset into the bound buffer and is never dereferenced on the CPU.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+impl VertexAttribute {
+    pub fn new(
+        index: u32,
+        size: i32,
+        r#type: GLenum,
+        normalized: GLboolean,
+        stride: GLsizei,
+        pointer: *const c_void,
+    ) -> VertexAttribute {
         unsafe {
-            gl::UniformMatrix4fv(
-                self.uniform_ids[uniform_name],
-                1,
-                gl::FALSE,
-                matrix.as_ptr(),
-            )
+            gl::VertexAttribPointer(index, size, r#type, normalized, stride, pointer);
+        }
+
+        VertexAttribute { index }
+    }
+
+    /// Create an integer attribute via `glVertexAttribIPointer`. Unlike `new`,
+    /// values are not converted to floats, so the shader input must be an
+    /// integer type such as `uvec4`.
+    pub fn new_integer(
+        index: u32,
+        size: i32,
+        r#type: GLenum,
+        stride: GLsizei,
+        pointer: *const c_void,
+    ) -> VertexAttribute {
+        unsafe {
+            gl::VertexAttribIPointer(index, size, r#type, stride, pointer);
+        }
+
+        VertexAttribute { index }
+    }
+
+    /// Enable the attribute on the bound VAO. Skipped if the wrappers already
+    /// enabled it on that VAO, see `reset_state_cache`.
+    pub fn enable(&self) {
+        enable_vertex_attrib_array(self.index);
+    }
+
+    pub fn disable(&self) {
+        unsafe {
+            gl::DisableVertexAttribArray(self.index);
+        }
+        STATE_CACHE.with(|cache| {
+            let mut cache = cache.borrow_mut();
+            if let Some(vao) = cache.bound_vao {
+                cache.enabled_attributes.remove(&(vao, self.index));
+            }
+        });
+    }
+
+    /// Advance this attribute once per `divisor` instances instead of once per
+    /// vertex. A divisor of 0 restores per-vertex behaviour.
+    pub fn set_divisor(&self, divisor: u32) {
+        unsafe {
+            gl::VertexAttribDivisor(self.index, divisor);
+        }
+    }
+}
+
+/// # Vertex Layout
+/// Describes an interleaved vertex layout, computing the stride and attribute
+/// offsets automatically. Attributes get consecutive indices in push order.
+///
+/// ## Example
+/// ```ignore
+/// // position: vec3, uv: vec2
+/// let layout = VertexLayout::new().push_f32(3).push_f32(2);
+///
+/// vao.bind();
+/// vbo.bind();
+/// layout.apply();
+/// ```
+#[derive(Default)]
+pub struct VertexLayout {
+    elements: Vec<VertexLayoutElement>,
+    stride: usize,
+}
+
+struct VertexLayoutElement {
+    count: i32,
+    r#type: GLenum,
+    integer: bool,
+    offset: usize,
+}
+
+impl VertexLayout {
+    pub fn new() -> VertexLayout {
+        VertexLayout::default()
+    }
+
+    /// Append an attribute made of `count` floats.
+    pub fn push_f32(self, count: i32) -> VertexLayout {
+        self.push(count, gl::FLOAT, false, mem::size_of::<GLfloat>())
+    }
+
+    /// Append an integer attribute made of `count` `i32`s.
+    pub fn push_i32(self, count: i32) -> VertexLayout {
+        self.push(count, gl::INT, true, mem::size_of::<GLint>())
+    }
+
+    /// Append an integer attribute made of `count` `u32`s.
+    pub fn push_u32(self, count: i32) -> VertexLayout {
+        self.push(count, gl::UNSIGNED_INT, true, mem::size_of::<GLuint>())
+    }
+
+    fn push(mut self, count: i32, r#type: GLenum, integer: bool, size: usize) -> VertexLayout {
+        self.elements.push(VertexLayoutElement {
+            count,
+            r#type,
+            integer,
+            offset: self.stride,
+        });
+        self.stride += count as usize * size;
+        self
+    }
+
+    /// Size of one vertex in bytes.
+    pub fn stride(&self) -> usize {
+        self.stride
+    }
+
+    /// Byte offset of attribute `index` within a vertex, or `None` if fewer
+    /// attributes were pushed.
+    pub fn offset(&self, index: usize) -> Option<usize> {
+        self.elements.get(index).map(|element| element.offset)
+    }
+
+    /// Specify and enable every attribute on the currently bound VAO and VBO.
+    pub fn apply(&self) {
+        for (index, element) in self.elements.iter().enumerate() {
+            let pointer = element.offset as *const c_void;
+            let attribute = if element.integer {
+                VertexAttribute::new_integer(
+                    index as u32,
+                    element.count,
+                    element.r#type,
+                    self.stride as GLsizei,
+                    pointer,
+                )
+            } else {
+                VertexAttribute::new(
+                    index as u32,
+                    element.count,
+                    element.r#type,
+                    gl::FALSE,
+                    self.stride as GLsizei,
+                    pointer,
+                )
+            };
+            attribute.enable();
+        }
+        check_gl_error("VertexLayout::apply");
+    }
+
+    /// Specify every attribute on the bound VAO with the separate format API,
+    /// reading from buffer binding `binding_index`. Attach buffers with
+    /// `vao.bind_vertex_buffer(binding_index, &vbo, 0, layout.stride() as i32)`.
+    pub fn apply_format(&self, vao: &Vao, binding_index: u32) {
+        for (index, element) in self.elements.iter().enumerate() {
+            let index = index as u32;
+            let offset = element.offset as u32;
+            if element.integer {
+                vao.set_attrib_integer_format(index, element.count, element.r#type, offset);
+            } else {
+                vao.set_attrib_format(index, element.count, element.r#type, false, offset);
+            }
+            vao.set_attrib_binding(index, binding_index);
+        }
+        check_gl_error("VertexLayout::apply_format");
+    }
+}
+
+/// # Texture 2D
+/// A two dimensional texture. Like the other wrappers, the texture has to be
+/// bound before loading data or changing its parameters.
+///
+/// ## Example
+/// ```ignore
+/// let texture = Texture2D::new();
+/// texture.bind();
+///
+/// texture.load_from_rgba(width, height, &pixels);
+/// texture.set_wrap_mode(gl::REPEAT, gl::REPEAT);
+/// texture.set_filter(gl::LINEAR_MIPMAP_LINEAR, gl::LINEAR);
+/// texture.generate_mipmaps();
+/// ```
+pub struct Texture2D {
+    id: GLuint,
+}
+
+impl Texture2D {
+    pub fn new() -> Texture2D {
+        let mut id = 0;
+        unsafe {
+            // Created names exist right away, so they can be labelled before
+            // their first bind.
+            if supports_dsa() {
+                gl::CreateTextures(gl::TEXTURE_2D, 1, &mut id);
+            } else {
+                gl::GenTextures(1, &mut id);
+            }
+        }
+
+        Texture2D { id }
+    }
+
+    /// Release ownership of the GL object without deleting it.
+    pub fn into_raw(mut self) -> GLuint {
+        mem::replace(&mut self.id, 0)
+    }
+
+    /// The GL name of the texture, which stays owned by the wrapper.
+    pub(crate) fn id(&self) -> GLuint {
+        self.id
+    }
+
+    /// Take ownership of an existing `GL_TEXTURE_2D` texture.
+    ///
+    /// # Safety
+    /// `id` must be a `GL_TEXTURE_2D` texture name that nothing else will delete.
+    pub unsafe fn from_raw(id: GLuint) -> Texture2D {
+        Texture2D { id }
+    }
+
+    /// Create a texture with immutable storage for `levels` mip levels, e.g.
+    /// `gl::RGBA8` or `gl::SRGB8_ALPHA8`. Fill it with `update_rgba`.
+    ///
+    /// Uses direct state access when the context supports it. Otherwise the
+    /// texture is bound to create it and left bound.
+    pub fn with_storage(
+        width: i32,
+        height: i32,
+        levels: i32,
+        internal_format: GLenum,
+    ) -> Texture2D {
+        let mut id = 0;
+        unsafe {
+            if supports_dsa() {
+                gl::CreateTextures(gl::TEXTURE_2D, 1, &mut id);
+                gl::TextureStorage2D(id, levels, internal_format, width, height);
+            } else {
+                gl::GenTextures(1, &mut id);
+                gl::BindTexture(gl::TEXTURE_2D, id);
+                gl::TexStorage2D(gl::TEXTURE_2D, levels, internal_format, width, height);
+            }
+        }
+        check_gl_error("Texture2D::with_storage");
+
+        Texture2D { id }
+    }
+
+    /// Overwrite a region of mip level 0 with tightly packed 8-bit RGBA pixels.
+    /// The texture must already have storage, e.g. from `with_storage`.
+    ///
+    /// Uses direct state access when the context supports it, so the texture
+    /// doesn't have to be bound. Otherwise it is bound and left bound.
+    pub fn update_rgba(&self, x: i32, y: i32, width: i32, height: i32, data: &[u8]) {
+        assert_eq!(
+            data.len(),
+            width as usize * height as usize * 4,
+            "Pixel data does not match the region size"
+        );
+
+        unsafe {
+            if supports_dsa() {
+                gl::TextureSubImage2D(
+                    self.id,
+                    0,
+                    x,
+                    y,
+                    width,
+                    height,
+                    gl::RGBA,
+                    gl::UNSIGNED_BYTE,
+                    data.as_ptr() as *const c_void,
+                );
+            } else {
+                self.bind();
+                gl::TexSubImage2D(
+                    gl::TEXTURE_2D,
+                    0,
+                    x,
+                    y,
+                    width,
+                    height,
+                    gl::RGBA,
+                    gl::UNSIGNED_BYTE,
+                    data.as_ptr() as *const c_void,
+                );
+            }
+        }
+        check_gl_error("Texture2D::update_rgba");
+    }
+
+    /// Create an empty floating-point texture, e.g. `gl::RGBA16F` for an HDR
+    /// render target. `internal_format` may have one to four channels, such as
+    /// `gl::R32F` or `gl::RG16F`. It uses linear filtering, clamps to the edge
+    /// and is left unbound.
+    pub fn new_float(width: i32, height: i32, internal_format: GLenum) -> Texture2D {
+        let format = match internal_format {
+            gl::R16F | gl::R32F => gl::RED,
+            gl::RG16F | gl::RG32F => gl::RG,
+            gl::RGB16F | gl::RGB32F | gl::R11F_G11F_B10F => gl::RGB,
+            _ => gl::RGBA,
+        };
+
+        let texture = Texture2D::new();
+        texture.bind();
+        unsafe {
+            gl::TexImage2D(
+                gl::TEXTURE_2D,
+                0,
+                internal_format as GLint,
+                width,
+                height,
+                0,
+                format,
+                gl::FLOAT,
+                ptr::null(),
+            );
+        }
+        texture.set_filter(gl::LINEAR, gl::LINEAR);
+        texture.set_wrap_mode(gl::CLAMP_TO_EDGE, gl::CLAMP_TO_EDGE);
+        check_gl_error("Texture2D::new_float");
+        texture.unbind();
+
+        texture
+    }
+
+    /// Create a 24-bit depth texture for `Framebuffer::attach_depth_texture`,
+    /// e.g. a shadow map. It uses linear filtering, clamps to the edge and is
+    /// left unbound.
+    pub fn new_depth(width: i32, height: i32) -> Texture2D {
+        let texture = Texture2D::new();
+        texture.bind();
+        unsafe {
+            gl::TexImage2D(
+                gl::TEXTURE_2D,
+                0,
+                gl::DEPTH_COMPONENT24 as GLint,
+                width,
+                height,
+                0,
+                gl::DEPTH_COMPONENT,
+                gl::FLOAT,
+                ptr::null(),
+            );
+        }
+        texture.set_filter(gl::LINEAR, gl::LINEAR);
+        texture.set_wrap_mode(gl::CLAMP_TO_EDGE, gl::CLAMP_TO_EDGE);
+        check_gl_error("Texture2D::new_depth");
+        texture.unbind();
+
+        texture
+    }
+
+    /// Make a depth texture compare against the reference value instead of
+    /// returning depth, as a `sampler2DShadow` expects. `func` is usually
+    /// `gl::LEQUAL`. The texture has to be bound.
+    pub fn enable_depth_comparison(&self, func: GLenum) {
+        unsafe {
+            gl::TexParameteri(
+                gl::TEXTURE_2D,
+                gl::TEXTURE_COMPARE_MODE,
+                gl::COMPARE_REF_TO_TEXTURE as GLint,
+            );
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_COMPARE_FUNC, func as GLint);
+        }
+    }
+
+    pub fn disable_depth_comparison(&self) {
+        unsafe {
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_COMPARE_MODE, gl::NONE as GLint);
+        }
+    }
+
+    pub fn bind(&self) {
+        unsafe {
+            gl::BindTexture(gl::TEXTURE_2D, self.id);
+        }
+    }
+
+    pub fn unbind(&self) {
+        unsafe {
+            gl::BindTexture(gl::TEXTURE_2D, 0);
+        }
+    }
+
+    /// Bind to texture unit `unit`, leaving that unit active. The shader's
+    /// sampler uniform has to be set to the same unit.
+    ///
+    /// ## Example
+    /// ```ignore
+    /// albedo.bind_unit(0);
+    /// normal_map.bind_unit(1);
+    ///
+    /// program.set_uniform_i32("albedo", 0);
+    /// program.set_uniform_i32("normal_map", 1);
+    /// ```
+    pub fn bind_unit(&self, unit: u32) {
+        set_active_texture(unit);
+        self.bind();
+    }
+
+    /// Bind mip `level` to image unit `unit` for `imageLoad`/`imageStore` in
+    /// shaders. `access` is `gl::READ_ONLY`, `gl::WRITE_ONLY` or
+    /// `gl::READ_WRITE`, and `format` must match the shader's layout qualifier,
+    /// e.g. `gl::RGBA8` for `layout(rgba8)` or `gl::RGBA32F` for `layout(rgba32f)`.
+    ///
+    /// ## Example
+    /// ```ignore
+    /// source.bind_image(0, gl::READ_ONLY, gl::RGBA8, 0);
+    /// target.bind_image(1, gl::WRITE_ONLY, gl::RGBA8, 0);
+    ///
+    /// blur.bind();
+    /// blur.dispatch(width / 16, height / 16, 1);
+    /// memory_barrier(gl::SHADER_IMAGE_ACCESS_BARRIER_BIT);
+    /// ```
+    pub fn bind_image(&self, unit: u32, access: GLenum, format: GLenum, level: i32) {
+        unsafe {
+            gl::BindImageTexture(unit, self.id, level, gl::FALSE, 0, access, format);
+        }
+        check_gl_error("Texture2D::bind_image");
+    }
+
+    /// Bind until the returned guard is dropped.
+    pub fn bind_scoped(&self) -> BindGuard<'_, Texture2D> {
+        BindGuard::new(self)
+    }
+
+    /// Name the texture in debugging tools such as RenderDoc. Does nothing
+    /// without `KHR_debug`.
+    ///
+    /// Without direct state access the texture has to have been bound once,
+    /// since GL raises `GL_INVALID_VALUE` for names that don't exist yet.
+    pub fn set_label(&self, label: &str) {
+        set_object_label(gl::TEXTURE, self.id, label);
+    }
+
+    /// Upload tightly packed 8-bit RGBA pixels.
+    pub fn load_from_rgba(&self, width: i32, height: i32, data: &[u8]) {
+        self.load_u8_pixels(gl::RGBA8, gl::RGBA, 4, width, height, data);
+        check_gl_error("Texture2D::load_from_rgba");
+    }
+
+    /// Upload tightly packed 8-bit RGB pixels.
+    pub fn load_from_rgb(&self, width: i32, height: i32, data: &[u8]) {
+        self.load_u8_pixels(gl::RGB8, gl::RGB, 3, width, height, data);
+        check_gl_error("Texture2D::load_from_rgb");
+    }
+
+    /// Upload tightly packed single-channel 8-bit pixels, e.g. a glyph atlas.
+    /// Shaders read the value from the red channel.
+    pub fn load_from_r8(&self, width: i32, height: i32, data: &[u8]) {
+        self.load_u8_pixels(gl::R8, gl::RED, 1, width, height, data);
+        check_gl_error("Texture2D::load_from_r8");
+    }
+
+    /// Upload tightly packed 8-bit RGBA pixels whose color channels are sRGB
+    /// encoded. Sampling returns linear values.
+    ///
+    /// Use this for textures authored as colors, such as albedo and emissive
+    /// maps. Data textures like normal, roughness or height maps are already
+    /// linear and belong in `load_from_rgba`.
+    pub fn load_from_srgba(&self, width: i32, height: i32, data: &[u8]) {
+        self.load_u8_pixels(gl::SRGB8_ALPHA8, gl::RGBA, 4, width, height, data);
+        check_gl_error("Texture2D::load_from_srgba");
+    }
+
+    /// Upload tightly packed 8-bit sRGB encoded RGB pixels. See `load_from_srgba`.
+    pub fn load_from_srgb(&self, width: i32, height: i32, data: &[u8]) {
+        self.load_u8_pixels(gl::SRGB8, gl::RGB, 3, width, height, data);
+        check_gl_error("Texture2D::load_from_srgb");
+    }
+
+    /// Upload tightly packed floating-point RGB pixels as `GL_RGB16F`.
+    pub fn load_from_rgb_f32(&self, width: i32, height: i32, data: &[f32]) {
+        assert_eq!(
+            data.len(),
+            width as usize * height as usize * 3,
+            "Pixel data does not match the texture size"
+        );
+
+        unsafe {
+            gl::TexImage2D(
+                gl::TEXTURE_2D,
+                0,
+                gl::RGB16F as GLint,
+                width,
+                height,
+                0,
+                gl::RGB,
+                gl::FLOAT,
+                data.as_ptr() as *const c_void,
+            );
+        }
+        check_gl_error("Texture2D::load_from_rgb_f32");
+    }
+
+    fn load_u8_pixels(
+        &self,
+        internal_format: GLenum,
+        format: GLenum,
+        channels: usize,
+        width: i32,
+        height: i32,
+        data: &[u8],
+    ) {
+        assert_eq!(
+            data.len(),
+            width as usize * height as usize * channels,
+            "Pixel data does not match the texture size"
+        );
+
+        // Rows of tightly packed 8-bit data are not always a multiple of the
+        // default 4-byte alignment.
+        with_pixel_alignment(gl::UNPACK_ALIGNMENT, 1, || unsafe {
+            gl::TexImage2D(
+                gl::TEXTURE_2D,
+                0,
+                internal_format as GLint,
+                width,
+                height,
+                0,
+                format,
+                gl::UNSIGNED_BYTE,
+                data.as_ptr() as *const c_void,
+            );
+        });
+    }
+
+    /// Load a PNG, JPEG or any other format supported by the `image` crate.
+    ///
+    /// The image is flipped vertically so that UV (0, 0) is the bottom-left
+    /// corner, as OpenGL expects. Mipmaps are generated and the texture is left
+    /// unbound.
+    #[cfg(feature = "image")]
+    pub fn from_file(path: &str) -> Result<Texture2D, TextureError> {
+        Texture2D::load_file(path, false)
+    }
+
+    /// Like `from_file`, but treats the image as sRGB encoded. See
+    /// `load_from_srgba` for which textures need this.
+    #[cfg(feature = "image")]
+    pub fn from_file_srgb(path: &str) -> Result<Texture2D, TextureError> {
+        Texture2D::load_file(path, true)
+    }
+
+    #[cfg(feature = "image")]
+    fn load_file(path: &str, srgb: bool) -> Result<Texture2D, TextureError> {
+        let image = image::open(path)
+            .map_err(|source| TextureError::Image {
+                path: path.to_string(),
+                source,
+            })?
+            .flipv();
+
+        let texture = Texture2D::new();
+        texture.bind();
+
+        match image {
+            image::DynamicImage::ImageRgb8(image) => {
+                let (width, height) = (image.width() as i32, image.height() as i32);
+                if srgb {
+                    texture.load_from_srgb(width, height, &image);
+                } else {
+                    texture.load_from_rgb(width, height, &image);
+                }
+            }
+            image => {
+                let image = image.into_rgba8();
+                let (width, height) = (image.width() as i32, image.height() as i32);
+                if srgb {
+                    texture.load_from_srgba(width, height, &image);
+                } else {
+                    texture.load_from_rgba(width, height, &image);
+                }
+            }
+        }
+
+        texture.set_wrap_mode(gl::REPEAT, gl::REPEAT);
+        texture.set_filter(gl::LINEAR_MIPMAP_LINEAR, gl::LINEAR);
+        texture.generate_mipmaps();
+        texture.unbind();
+
+        Ok(texture)
+    }
+
+    /// Load a Radiance `.hdr` image (or any format the `image` crate decodes)
+    /// as a half-float RGB texture, e.g. an environment map for image-based
+    /// lighting. Values above 1.0 are kept. The image is flipped like in
+    /// `from_file`, clamped to the edge and left unbound.
+    #[cfg(feature = "image")]
+    pub fn from_hdr(path: &str) -> Result<Texture2D, TextureError> {
+        let image = image::open(path)
+            .map_err(|source| TextureError::Image {
+                path: path.to_string(),
+                source,
+            })?
+            .flipv()
+            .into_rgb32f();
+
+        let texture = Texture2D::new();
+        texture.bind();
+        texture.load_from_rgb_f32(image.width() as i32, image.height() as i32, &image);
+        texture.set_wrap_mode(gl::CLAMP_TO_EDGE, gl::CLAMP_TO_EDGE);
+        texture.set_filter(gl::LINEAR, gl::LINEAR);
+        texture.unbind();
+
+        Ok(texture)
+    }
+
+    pub fn set_wrap_mode(&self, wrap_s: GLenum, wrap_t: GLenum) {
+        unsafe {
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_S, wrap_s as GLint);
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_T, wrap_t as GLint);
+        }
+    }
+
+    pub fn set_filter(&self, min_filter: GLenum, mag_filter: GLenum) {
+        unsafe {
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, min_filter as GLint);
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, mag_filter as GLint);
+        }
+    }
+
+    /// Enable anisotropic filtering, clamped to what the driver supports.
+    /// Does nothing if the driver doesn't support anisotropic filtering.
+    pub fn set_anisotropy(&self, level: f32) {
+        if !has_extension("GL_ARB_texture_filter_anisotropic")
+            && !has_extension("GL_EXT_texture_filter_anisotropic")
+        {
+            return;
+        }
+
+        let mut max_level = 1.0;
+        unsafe {
+            gl::GetFloatv(MAX_TEXTURE_MAX_ANISOTROPY, &mut max_level);
+            gl::TexParameterf(
+                gl::TEXTURE_2D,
+                TEXTURE_MAX_ANISOTROPY,
+                level.clamp(1.0, max_level),
+            );
+        }
+    }
+
+    pub fn generate_mipmaps(&self) {
+        unsafe {
+            gl::GenerateMipmap(gl::TEXTURE_2D);
+        }
+        check_gl_error("Texture2D::generate_mipmaps");
+    }
+
+    /// Read mip level 0 back and return the `(x, y)` coordinates of every texel
+    /// with a NaN or infinite channel, with `y = 0` as the bottom row. Meant for
+    /// tracking down bad math in float render targets. Binds the texture.
+    ///
+    /// ## Example
+    /// ```ignore
+    /// let bad_pixels = hdr_target.find_nan_pixels();
+    /// if let Some((x, y)) = bad_pixels.first() {
+    ///     logger::warn!("{} non-finite pixels, first at ({}, {})", bad_pixels.len(), x, y);
+    /// }
+    /// ```
+    #[cfg(debug_assertions)]
+    pub fn find_nan_pixels(&self) -> Vec<(u32, u32)> {
+        self.bind();
+
+        let (mut width, mut height) = (0, 0);
+        unsafe {
+            gl::GetTexLevelParameteriv(gl::TEXTURE_2D, 0, gl::TEXTURE_WIDTH, &mut width);
+            gl::GetTexLevelParameteriv(gl::TEXTURE_2D, 0, gl::TEXTURE_HEIGHT, &mut height);
         }
+        let width = width.max(0) as usize;
+        let height = height.max(0) as usize;
+
+        let mut texels = vec![0f32; width * height * 4];
+        unsafe {
+            gl::GetTexImage(
+                gl::TEXTURE_2D,
+                0,
+                gl::RGBA,
+                gl::FLOAT,
+                texels.as_mut_ptr() as *mut c_void,
+            );
+        }
+        check_gl_error("Texture2D::find_nan_pixels");
+
+        texels
+            .chunks_exact(4)
+            .enumerate()
+            .filter(|(_, texel)| texel.iter().any(|channel| !channel.is_finite()))
+            .map(|(index, _)| ((index % width) as u32, (index / width) as u32))
+            .collect()
+    }
+}
+
+// Core in GL 4.6, so not part of the generated 4.5 bindings.
+const TEXTURE_MAX_ANISOTROPY: GLenum = 0x84FE;
+const MAX_TEXTURE_MAX_ANISOTROPY: GLenum = 0x84FF;
+
+#[cfg(feature = "bindless")]
+impl Texture2D {
+    /// Make the texture resident and return its bindless handle, which shaders
+    /// can use as a `sampler2D` without binding the texture to a unit. The
+    /// texture's parameters can no longer be changed while it is resident.
+    ///
+    /// Fails if the context lacks `GL_ARB_bindless_texture` or
+    /// `load_bindless_with` was not called.
+    pub fn make_resident(&self) -> Result<u64, TextureError> {
+        let functions = bindless_functions()?;
+        unsafe {
+            let handle = (functions.get_texture_handle)(self.id);
+            (functions.make_texture_handle_resident)(handle);
+            Ok(handle)
+        }
+    }
+
+    pub fn make_non_resident(&self) -> Result<(), TextureError> {
+        let functions = bindless_functions()?;
+        unsafe {
+            // A texture always returns the same handle.
+            let handle = (functions.get_texture_handle)(self.id);
+            (functions.make_texture_handle_non_resident)(handle);
+        }
+        Ok(())
+    }
+}
+
+/// `GL_ARB_bindless_texture` entry points, which the generated bindings don't
+/// include.
+#[cfg(feature = "bindless")]
+#[derive(Clone, Copy)]
+struct BindlessFunctions {
+    get_texture_handle: GetTextureHandleFn,
+    make_texture_handle_resident: TextureHandleFn,
+    make_texture_handle_non_resident: TextureHandleFn,
+}
+
+#[cfg(feature = "bindless")]
+type GetTextureHandleFn = unsafe extern "system" fn(GLuint) -> GLuint64;
+#[cfg(feature = "bindless")]
+type TextureHandleFn = unsafe extern "system" fn(GLuint64);
+
+#[cfg(feature = "bindless")]
+thread_local! {
+    static BINDLESS_FUNCTIONS: Cell<Option<BindlessFunctions>> =
+        const { Cell::new(None) };
+}
+
+/// Load the bindless texture functions with the same loader passed to
+/// `gl::load_with`. `Window::init_gl` does this already.
+#[cfg(feature = "bindless")]
+pub fn load_bindless_with<F: FnMut(&'static str) -> *const c_void>(mut loader: F) {
+    let get_texture_handle = loader("glGetTextureHandleARB");
+    let make_texture_handle_resident = loader("glMakeTextureHandleResidentARB");
+    let make_texture_handle_non_resident = loader("glMakeTextureHandleNonResidentARB");
+
+    let functions = if get_texture_handle.is_null()
+        || make_texture_handle_resident.is_null()
+        || make_texture_handle_non_resident.is_null()
+    {
+        None
+    } else {
+        unsafe {
+            Some(BindlessFunctions {
+                get_texture_handle: mem::transmute::<*const c_void, GetTextureHandleFn>(
+                    get_texture_handle,
+                ),
+                make_texture_handle_resident: mem::transmute::<*const c_void, TextureHandleFn>(
+                    make_texture_handle_resident,
+                ),
+                make_texture_handle_non_resident: mem::transmute::<*const c_void, TextureHandleFn>(
+                    make_texture_handle_non_resident,
+                ),
+            })
+        }
+    };
+    BINDLESS_FUNCTIONS.with(|cell| cell.set(functions));
+}
+
+#[cfg(feature = "bindless")]
+fn bindless_functions() -> Result<BindlessFunctions, TextureError> {
+    if !has_extension("GL_ARB_bindless_texture") {
+        return Err(TextureError::BindlessUnsupported);
+    }
+
+    BINDLESS_FUNCTIONS
+        .with(|cell| cell.get())
+        .ok_or(TextureError::BindlessUnsupported)
+}
+
+impl Default for Texture2D {
+    fn default() -> Texture2D {
+        Texture2D::new()
     }
 }
+
+/// Deletes the texture.
+///
+/// Must be dropped on the thread that owns the GL context.
+impl Drop for Texture2D {
+    fn drop(&mut self) {
+        if self.id != 0 {
+            unsafe {
+                gl::DeleteTextures(1, &self.id);
+            }
+        }
+    }
+}
+
+impl Bind for Texture2D {
+    fn bind(&self) {
+        Texture2D::bind(self);
+    }
+
+    fn unbind(&self) {
+        Texture2D::unbind(self);
+    }
+}
+
+/// # Texture Array
+/// A stack of same-sized RGBA layers in a `GL_TEXTURE_2D_ARRAY`, sampled in
+/// GLSL with a `sampler2DArray` and a layer index. Like `Texture2D`, it has to be
+/// bound before uploading layers or changing parameters.
+///
+/// ## Example
+/// ```ignore
+/// let tiles = TextureArray::new(16, 16, tile_count);
+/// tiles.bind();
+///
+/// for (layer, pixels) in tile_pixels.iter().enumerate() {
+///     tiles.upload_layer(layer as i32, pixels);
+/// }
+/// tiles.set_filter(gl::NEAREST, gl::NEAREST);
+/// ```
+pub struct TextureArray {
+    id: GLuint,
+    width: i32,
+    height: i32,
+    layers: i32,
+}
+
+impl TextureArray {
+    /// Allocate storage for `layers` RGBA layers of `width` x `height`.
+    pub fn new(width: i32, height: i32, layers: i32) -> TextureArray {
+        let mut id = 0;
+        unsafe {
+            gl::GenTextures(1, &mut id);
+            gl::BindTexture(gl::TEXTURE_2D_ARRAY, id);
+            gl::TexImage3D(
+                gl::TEXTURE_2D_ARRAY,
+                0,
+                gl::RGBA8 as GLint,
+                width,
+                height,
+                layers,
+                0,
+                gl::RGBA,
+                gl::UNSIGNED_BYTE,
+                ptr::null(),
+            );
+            gl::BindTexture(gl::TEXTURE_2D_ARRAY, 0);
+        }
+        check_gl_error("TextureArray::new");
+
+        TextureArray {
+            id,
+            width,
+            height,
+            layers,
+        }
+    }
+
+    pub fn bind(&self) {
+        unsafe {
+            gl::BindTexture(gl::TEXTURE_2D_ARRAY, self.id);
+        }
+    }
+
+    pub fn unbind(&self) {
+        unsafe {
+            gl::BindTexture(gl::TEXTURE_2D_ARRAY, 0);
+        }
+    }
+
+    /// Upload tightly packed 8-bit RGBA pixels into one layer.
+    pub fn upload_layer(&self, index: i32, data: &[u8]) {
+        assert!(
+            (0..self.layers).contains(&index),
+            "Layer {} is out of range",
+            index
+        );
+        assert_eq!(
+            data.len(),
+            self.width as usize * self.height as usize * 4,
+            "RGBA data does not match the layer size"
+        );
+
+        unsafe {
+            gl::TexSubImage3D(
+                gl::TEXTURE_2D_ARRAY,
+                0,
+                0,
+                0,
+                index,
+                self.width,
+                self.height,
+                1,
+                gl::RGBA,
+                gl::UNSIGNED_BYTE,
+                data.as_ptr() as *const c_void,
+            );
+        }
+        check_gl_error("TextureArray::upload_layer");
+    }
+
+    pub fn set_wrap_mode(&self, wrap_s: GLenum, wrap_t: GLenum) {
+        unsafe {
+            gl::TexParameteri(gl::TEXTURE_2D_ARRAY, gl::TEXTURE_WRAP_S, wrap_s as GLint);
+            gl::TexParameteri(gl::TEXTURE_2D_ARRAY, gl::TEXTURE_WRAP_T, wrap_t as GLint);
+        }
+    }
+
+    pub fn set_filter(&self, min_filter: GLenum, mag_filter: GLenum) {
+        unsafe {
+            gl::TexParameteri(
+                gl::TEXTURE_2D_ARRAY,
+                gl::TEXTURE_MIN_FILTER,
+                min_filter as GLint,
+            );
+            gl::TexParameteri(
+                gl::TEXTURE_2D_ARRAY,
+                gl::TEXTURE_MAG_FILTER,
+                mag_filter as GLint,
+            );
+        }
+    }
+
+    pub fn generate_mipmaps(&self) {
+        unsafe {
+            gl::GenerateMipmap(gl::TEXTURE_2D_ARRAY);
+        }
+    }
+
+    pub fn layers(&self) -> i32 {
+        self.layers
+    }
+}
+
+/// Deletes the texture.
+///
+/// Must be dropped on the thread that owns the GL context.
+impl Drop for TextureArray {
+    fn drop(&mut self) {
+        if self.id != 0 {
+            unsafe {
+                gl::DeleteTextures(1, &self.id);
+            }
+        }
+    }
+}
+
+/// # Cubemap
+/// Six square faces in a `GL_TEXTURE_CUBE_MAP`, e.g. for skyboxes. Faces are
+/// indexed in GL order: +X, -X, +Y, -Y, +Z, -Z. The cubemap has to be bound
+/// before loading faces or changing parameters.
+///
+/// ## Example
+/// ```ignore
+/// let skybox = Cubemap::from_faces([
+///     "sky/right.png",
+///     "sky/left.png",
+///     "sky/top.png",
+///     "sky/bottom.png",
+///     "sky/front.png",
+///     "sky/back.png",
+/// ])?;
+/// skybox.bind();
+/// ```
+pub struct Cubemap {
+    id: GLuint,
+}
+
+impl Cubemap {
+    pub fn new() -> Cubemap {
+        let mut id = 0;
+        unsafe {
+            gl::GenTextures(1, &mut id);
+        }
+
+        Cubemap { id }
+    }
+
+    /// Release ownership of the GL object without deleting it.
+    pub fn into_raw(mut self) -> GLuint {
+        mem::replace(&mut self.id, 0)
+    }
+
+    /// Take ownership of an existing `GL_TEXTURE_CUBE_MAP` texture.
+    ///
+    /// # Safety
+    /// `id` must be a `GL_TEXTURE_CUBE_MAP` texture name that nothing else will delete.
+    pub unsafe fn from_raw(id: GLuint) -> Cubemap {
+        Cubemap { id }
+    }
+
+    pub fn bind(&self) {
+        unsafe {
+            gl::BindTexture(gl::TEXTURE_CUBE_MAP, self.id);
+        }
+    }
+
+    pub fn unbind(&self) {
+        unsafe {
+            gl::BindTexture(gl::TEXTURE_CUBE_MAP, 0);
+        }
+    }
+
+    /// Upload tightly packed 8-bit RGBA pixels to face `index` (0 is +X).
+    pub fn load_face(&self, index: u32, width: i32, height: i32, data: &[u8]) {
+        assert!(index < 6, "Cubemap face {} is out of range", index);
+        assert_eq!(
+            data.len(),
+            width as usize * height as usize * 4,
+            "RGBA data does not match the face size"
+        );
+
+        unsafe {
+            gl::TexImage2D(
+                gl::TEXTURE_CUBE_MAP_POSITIVE_X + index,
+                0,
+                gl::RGBA8 as GLint,
+                width,
+                height,
+                0,
+                gl::RGBA,
+                gl::UNSIGNED_BYTE,
+                data.as_ptr() as *const c_void,
+            );
+        }
+        check_gl_error("Cubemap::load_face");
+    }
+
+    /// Load the six faces from image files, in +X, -X, +Y, -Y, +Z, -Z order.
+    ///
+    /// Unlike `Texture2D::from_file` the faces are not flipped: cubemap
+    /// lookups use a top-left origin for each face. Edges are clamped so no
+    /// seams show between faces, and the cubemap is left unbound.
+    #[cfg(feature = "image")]
+    pub fn from_faces(paths: [&str; 6]) -> Result<Cubemap, TextureError> {
+        let cubemap = Cubemap::new();
+        cubemap.bind();
+
+        for (index, path) in paths.iter().enumerate() {
+            let image = image::open(path)
+                .map_err(|source| TextureError::Image {
+                    path: path.to_string(),
+                    source,
+                })?
+                .into_rgba8();
+
+            cubemap.load_face(
+                index as u32,
+                image.width() as i32,
+                image.height() as i32,
+                &image,
+            );
+        }
+
+        cubemap.set_filter(gl::LINEAR, gl::LINEAR);
+        unsafe {
+            gl::TexParameteri(
+                gl::TEXTURE_CUBE_MAP,
+                gl::TEXTURE_WRAP_S,
+                gl::CLAMP_TO_EDGE as GLint,
+            );
+            gl::TexParameteri(
+                gl::TEXTURE_CUBE_MAP,
+                gl::TEXTURE_WRAP_T,
+                gl::CLAMP_TO_EDGE as GLint,
+            );
+            gl::TexParameteri(
+                gl::TEXTURE_CUBE_MAP,
+                gl::TEXTURE_WRAP_R,
+                gl::CLAMP_TO_EDGE as GLint,
+            );
+        }
+        cubemap.unbind();
+
+        Ok(cubemap)
+    }
+
+    pub fn set_filter(&self, min_filter: GLenum, mag_filter: GLenum) {
+        unsafe {
+            gl::TexParameteri(
+                gl::TEXTURE_CUBE_MAP,
+                gl::TEXTURE_MIN_FILTER,
+                min_filter as GLint,
+            );
+            gl::TexParameteri(
+                gl::TEXTURE_CUBE_MAP,
+                gl::TEXTURE_MAG_FILTER,
+                mag_filter as GLint,
+            );
+        }
+    }
+}
+
+impl Default for Cubemap {
+    fn default() -> Cubemap {
+        Cubemap::new()
+    }
+}
+
+/// Deletes the texture.
+///
+/// Must be dropped on the thread that owns the GL context.
+impl Drop for Cubemap {
+    fn drop(&mut self) {
+        if self.id != 0 {
+            unsafe {
+                gl::DeleteTextures(1, &self.id);
+            }
+        }
+    }
+}
+
+/// # Sampler
+/// Sampling parameters kept separate from texture storage. While bound to a
+/// texture unit, they override the parameters of whatever texture is bound
+/// there, so one texture can be sampled differently in different passes.
+///
+/// ## Example
+/// ```ignore
+/// let pixelated = Sampler::new();
+/// pixelated.set_filter(gl::NEAREST, gl::NEAREST);
+///
+/// pixelated.bind(0);
+/// ```
+pub struct Sampler {
+    id: GLuint,
+}
+
+impl Sampler {
+    pub fn new() -> Sampler {
+        let mut id = 0;
+        unsafe {
+            gl::GenSamplers(1, &mut id);
+        }
+
+        Sampler { id }
+    }
+
+    /// Release ownership of the GL object without deleting it.
+    pub fn into_raw(mut self) -> GLuint {
+        mem::replace(&mut self.id, 0)
+    }
+
+    /// Take ownership of an existing sampler.
+    ///
+    /// # Safety
+    /// `id` must be a sampler name that nothing else will delete.
+    pub unsafe fn from_raw(id: GLuint) -> Sampler {
+        Sampler { id }
+    }
+
+    /// Bind to texture unit `unit`.
+    pub fn bind(&self, unit: u32) {
+        unsafe {
+            gl::BindSampler(unit, self.id);
+        }
+    }
+
+    pub fn unbind(&self, unit: u32) {
+        unsafe {
+            gl::BindSampler(unit, 0);
+        }
+    }
+
+    pub fn set_wrap_mode(&self, wrap_s: GLenum, wrap_t: GLenum) {
+        unsafe {
+            gl::SamplerParameteri(self.id, gl::TEXTURE_WRAP_S, wrap_s as GLint);
+            gl::SamplerParameteri(self.id, gl::TEXTURE_WRAP_T, wrap_t as GLint);
+        }
+    }
+
+    pub fn set_filter(&self, min_filter: GLenum, mag_filter: GLenum) {
+        unsafe {
+            gl::SamplerParameteri(self.id, gl::TEXTURE_MIN_FILTER, min_filter as GLint);
+            gl::SamplerParameteri(self.id, gl::TEXTURE_MAG_FILTER, mag_filter as GLint);
+        }
+    }
+}
+
+impl Default for Sampler {
+    fn default() -> Sampler {
+        Sampler::new()
+    }
+}
+
+/// Deletes the sampler.
+///
+/// Must be dropped on the thread that owns the GL context.
+impl Drop for Sampler {
+    fn drop(&mut self) {
+        if self.id != 0 {
+            unsafe {
+                gl::DeleteSamplers(1, &self.id);
+            }
+        }
+    }
+}
+
+/// # Renderbuffer
+/// Render target storage that can't be sampled, typically used for depth and
+/// stencil.
+///
+/// ## Example
+/// ```ignore
+/// let depth = Renderbuffer::new(gl::DEPTH24_STENCIL8, 1280, 720);
+///
+/// framebuffer.bind();
+/// depth.attach_to_framebuffer(gl::DEPTH_STENCIL_ATTACHMENT);
+/// ```
+pub struct Renderbuffer {
+    id: GLuint,
+}
+
+impl Renderbuffer {
+    pub fn new(internal_format: GLenum, width: i32, height: i32) -> Renderbuffer {
+        let mut id = 0;
+        unsafe {
+            gl::GenRenderbuffers(1, &mut id);
+            gl::BindRenderbuffer(gl::RENDERBUFFER, id);
+            gl::RenderbufferStorage(gl::RENDERBUFFER, internal_format, width, height);
+            gl::BindRenderbuffer(gl::RENDERBUFFER, 0);
+        }
+        check_gl_error("Renderbuffer::new");
+
+        Renderbuffer { id }
+    }
+
+    /// Create multisampled storage for MSAA rendering. Resolve it into a regular
+    /// framebuffer with `Framebuffer::blit_to` before sampling.
+    pub fn new_multisample(
+        internal_format: GLenum,
+        samples: i32,
+        width: i32,
+        height: i32,
+    ) -> Renderbuffer {
+        let mut id = 0;
+        unsafe {
+            gl::GenRenderbuffers(1, &mut id);
+            gl::BindRenderbuffer(gl::RENDERBUFFER, id);
+            gl::RenderbufferStorageMultisample(
+                gl::RENDERBUFFER,
+                samples,
+                internal_format,
+                width,
+                height,
+            );
+            gl::BindRenderbuffer(gl::RENDERBUFFER, 0);
+        }
+        check_gl_error("Renderbuffer::new_multisample");
+
+        Renderbuffer { id }
+    }
+
+    /// Release ownership of the GL object without deleting it.
+    pub fn into_raw(mut self) -> GLuint {
+        mem::replace(&mut self.id, 0)
+    }
+
+    /// Take ownership of an existing renderbuffer.
+    ///
+    /// # Safety
+    /// `id` must be a renderbuffer name that nothing else will delete.
+    pub unsafe fn from_raw(id: GLuint) -> Renderbuffer {
+        Renderbuffer { id }
+    }
+
+    pub fn bind(&self) {
+        unsafe {
+            gl::BindRenderbuffer(gl::RENDERBUFFER, self.id);
+        }
+    }
+
+    pub fn unbind(&self) {
+        unsafe {
+            gl::BindRenderbuffer(gl::RENDERBUFFER, 0);
+        }
+    }
+
+    /// Attach to the currently bound framebuffer.
+    pub fn attach_to_framebuffer(&self, attachment: GLenum) {
+        unsafe {
+            gl::FramebufferRenderbuffer(gl::FRAMEBUFFER, attachment, gl::RENDERBUFFER, self.id);
+        }
+    }
+}
+
+/// Deletes the renderbuffer.
+///
+/// Must be dropped on the thread that owns the GL context.
+impl Drop for Renderbuffer {
+    fn drop(&mut self) {
+        if self.id != 0 {
+            unsafe {
+                gl::DeleteRenderbuffers(1, &self.id);
+            }
+        }
+    }
+}
+
+/// # Framebuffer
+/// An off-screen render target. The framebuffer has to be bound before
+/// attaching anything to it.
+///
+/// ## Example
+/// ```ignore
+/// let framebuffer = Framebuffer::new();
+/// framebuffer.bind();
+///
+/// framebuffer.attach_color_texture(&texture, 0);
+/// framebuffer.attach_depth_renderbuffer(1280, 720);
+/// framebuffer.check_complete().expect("Framebuffer is incomplete");
+/// ```
+pub struct Framebuffer {
+    id: GLuint,
+    depth_renderbuffer: Option<Renderbuffer>,
+}
+
+impl Framebuffer {
+    pub fn new() -> Framebuffer {
+        let mut id = 0;
+        unsafe {
+            gl::GenFramebuffers(1, &mut id);
+        }
+
+        Framebuffer {
+            id,
+            depth_renderbuffer: None,
+        }
+    }
+
+    /// Release ownership of the GL object without deleting it. Returns the
+    /// framebuffer name and the name of the renderbuffer from
+    /// `attach_depth_renderbuffer`, if any. That renderbuffer stays attached
+    /// and the new owner has to delete it along with the framebuffer.
+    pub fn into_raw(mut self) -> (GLuint, Option<GLuint>) {
+        let depth_renderbuffer = self
+            .depth_renderbuffer
+            .take()
+            .map(|renderbuffer| renderbuffer.into_raw());
+        (mem::replace(&mut self.id, 0), depth_renderbuffer)
+    }
+
+    /// Take ownership of an existing framebuffer. Its attachments stay owned by
+    /// whoever created them.
+    ///
+    /// # Safety
+    /// `id` must be a framebuffer name that nothing else will delete.
+    pub unsafe fn from_raw(id: GLuint) -> Framebuffer {
+        Framebuffer {
+            id,
+            depth_renderbuffer: None,
+        }
+    }
+
+    pub fn bind(&self) {
+        unsafe {
+            gl::BindFramebuffer(gl::FRAMEBUFFER, self.id);
+        }
+    }
+
+    /// Bind the default framebuffer again.
+    pub fn unbind(&self) {
+        unsafe {
+            gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
+        }
+    }
+
+    pub fn attach_color_texture(&self, texture: &Texture2D, attachment_index: u32) {
+        unsafe {
+            gl::FramebufferTexture2D(
+                gl::FRAMEBUFFER,
+                gl::COLOR_ATTACHMENT0 + attachment_index,
+                gl::TEXTURE_2D,
+                texture.id,
+                0,
+            );
+        }
+        check_gl_error("Framebuffer::attach_color_texture");
+    }
+
+    /// Choose which color attachments fragment shader outputs are written to.
+    /// Output `location = i` goes to `GL_COLOR_ATTACHMENT0 + attachments[i]`.
+    /// Without this only attachment 0 is drawn to. The framebuffer has to be bound.
+    ///
+    /// ## Example
+    /// ```ignore
+    /// g_buffer.bind();
+    /// g_buffer.attach_color_texture(&position, 0);
+    /// g_buffer.attach_color_texture(&normal, 1);
+    /// g_buffer.attach_color_texture(&albedo, 2);
+    /// g_buffer.set_draw_buffers(&[0, 1, 2]);
+    /// ```
+    pub fn set_draw_buffers(&self, attachments: &[u32]) {
+        let buffers: Vec<GLenum> = attachments
+            .iter()
+            .map(|index| gl::COLOR_ATTACHMENT0 + index)
+            .collect();
+        unsafe {
+            gl::DrawBuffers(buffers.len() as GLsizei, buffers.as_ptr());
+        }
+        check_gl_error("Framebuffer::set_draw_buffers");
+    }
+
+    /// Create a depth/stencil renderbuffer owned by this framebuffer and attach it.
+    pub fn attach_depth_renderbuffer(&mut self, width: i32, height: i32) {
+        let renderbuffer = Renderbuffer::new(gl::DEPTH24_STENCIL8, width, height);
+        renderbuffer.attach_to_framebuffer(gl::DEPTH_STENCIL_ATTACHMENT);
+        self.depth_renderbuffer = Some(renderbuffer);
+    }
+
+    /// Attach a texture from `Texture2D::new_depth` as the depth buffer, so it
+    /// can be sampled after rendering.
+    pub fn attach_depth_texture(&self, texture: &Texture2D) {
+        unsafe {
+            gl::FramebufferTexture2D(
+                gl::FRAMEBUFFER,
+                gl::DEPTH_ATTACHMENT,
+                gl::TEXTURE_2D,
+                texture.id,
+                0,
+            );
+        }
+        check_gl_error("Framebuffer::attach_depth_texture");
+    }
+
+    /// Copy a `width` x `height` region into `other`, e.g. to resolve a
+    /// multisampled framebuffer. `mask` selects the buffers to copy and `filter`
+    /// must be `gl::NEAREST` when copying depth or stencil. Leaves the default
+    /// framebuffer bound.
+    pub fn blit_to(
+        &self,
+        other: &Framebuffer,
+        width: i32,
+        height: i32,
+        mask: GLbitfield,
+        filter: GLenum,
+    ) {
+        unsafe {
+            gl::BindFramebuffer(gl::READ_FRAMEBUFFER, self.id);
+            gl::BindFramebuffer(gl::DRAW_FRAMEBUFFER, other.id);
+            gl::BlitFramebuffer(0, 0, width, height, 0, 0, width, height, mask, filter);
+            gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
+        }
+        check_gl_error("Framebuffer::blit_to");
+    }
+
+    /// Returns the framebuffer status if it is not `GL_FRAMEBUFFER_COMPLETE`.
+    pub fn check_complete(&self) -> Result<(), GLenum> {
+        let status = unsafe { gl::CheckFramebufferStatus(gl::FRAMEBUFFER) };
+        if status == gl::FRAMEBUFFER_COMPLETE {
+            Ok(())
+        } else {
+            Err(status)
+        }
+    }
+}
+
+impl Default for Framebuffer {
+    fn default() -> Framebuffer {
+        Framebuffer::new()
+    }
+}
+
+/// Deletes the framebuffer and any renderbuffer it owns.
+///
+/// Must be dropped on the thread that owns the GL context.
+impl Drop for Framebuffer {
+    fn drop(&mut self) {
+        if self.id != 0 {
+            unsafe {
+                gl::DeleteFramebuffers(1, &self.id);
+            }
+        }
+    }
+}
+
+/// # GPU Timer
+/// Measures how long the GPU spends on the commands between `begin` and `end`.
+/// The result arrives asynchronously, usually a frame or two later.
+///
+/// ## Example
+/// ```ignore
+/// let mut timer = GpuTimer::new();
+///
+/// timer.begin();
+/// render_scene();
+/// timer.end();
+///
+/// if let Some(ms) = timer.elapsed_ms() {
+///     logger::info!("Scene took {:.2}ms", ms);
+/// }
+/// ```
+pub struct GpuTimer {
+    id: GLuint,
+    ended: bool,
+}
+
+impl GpuTimer {
+    pub fn new() -> GpuTimer {
+        let mut id = 0;
+        unsafe {
+            gl::GenQueries(1, &mut id);
+        }
+
+        GpuTimer { id, ended: false }
+    }
+
+    /// Start timing. The previous measurement is discarded.
+    pub fn begin(&mut self) {
+        unsafe {
+            gl::BeginQuery(gl::TIME_ELAPSED, self.id);
+        }
+        self.ended = false;
+    }
+
+    pub fn end(&mut self) {
+        unsafe {
+            gl::EndQuery(gl::TIME_ELAPSED);
+        }
+        self.ended = true;
+    }
+
+    /// The measured time in milliseconds, or `None` if the GPU hasn't finished
+    /// the timed commands yet.
+    pub fn elapsed_ms(&self) -> Option<f64> {
+        if !self.ended {
+            return None;
+        }
+
+        let mut available = 0;
+        unsafe {
+            gl::GetQueryObjectiv(self.id, gl::QUERY_RESULT_AVAILABLE, &mut available);
+        }
+        if available == 0 {
+            return None;
+        }
+
+        let mut nanoseconds = 0;
+        unsafe {
+            gl::GetQueryObjectui64v(self.id, gl::QUERY_RESULT, &mut nanoseconds);
+        }
+
+        Some(nanoseconds as f64 / 1_000_000.0)
+    }
+}
+
+impl Default for GpuTimer {
+    fn default() -> GpuTimer {
+        GpuTimer::new()
+    }
+}
+
+/// Deletes the query.
+///
+/// Must be dropped on the thread that owns the GL context.
+impl Drop for GpuTimer {
+    fn drop(&mut self) {
+        if self.id != 0 {
+            unsafe {
+                gl::DeleteQueries(1, &self.id);
+            }
+        }
+    }
+}
+
+/// # Occlusion Query
+/// Counts the samples that pass the depth test between `begin` and `end`. Draw
+/// a cheap bounding volume inside the query, then skip the real object if none
+/// of it was visible.
+///
+/// `target` is `gl::SAMPLES_PASSED` for an exact count or
+/// `gl::ANY_SAMPLES_PASSED`, which may be faster and only reports 0 or 1.
+///
+/// ## Example
+/// ```ignore
+/// let mut query = OcclusionQuery::new(gl::ANY_SAMPLES_PASSED);
+///
+/// query.begin();
+/// bounding_box.draw();
+/// query.end();
+///
+/// query.begin_conditional_render(gl::QUERY_WAIT);
+/// model.draw();
+/// end_conditional_render();
+/// ```
+pub struct OcclusionQuery {
+    id: GLuint,
+    target: GLenum,
+    ended: bool,
+}
+
+impl OcclusionQuery {
+    pub fn new(target: GLenum) -> OcclusionQuery {
+        let mut id = 0;
+        unsafe {
+            gl::GenQueries(1, &mut id);
+        }
+
+        OcclusionQuery {
+            id,
+            target,
+            ended: false,
+        }
+    }
+
+    /// Start counting. The previous result is discarded.
+    pub fn begin(&mut self) {
+        unsafe {
+            gl::BeginQuery(self.target, self.id);
+        }
+        self.ended = false;
+    }
+
+    pub fn end(&mut self) {
+        unsafe {
+            gl::EndQuery(self.target);
+        }
+        self.ended = true;
+    }
+
+    /// The number of samples that passed, or `None` if the result isn't
+    /// available yet.
+    pub fn samples_passed(&self) -> Option<u32> {
+        if !self.ended {
+            return None;
+        }
+
+        let mut available = 0;
+        unsafe {
+            gl::GetQueryObjectiv(self.id, gl::QUERY_RESULT_AVAILABLE, &mut available);
+        }
+        if available == 0 {
+            return None;
+        }
+
+        let mut samples = 0;
+        unsafe {
+            gl::GetQueryObjectuiv(self.id, gl::QUERY_RESULT, &mut samples);
+        }
+
+        Some(samples)
+    }
+
+    /// Let the GPU discard the following draws if the query found no samples,
+    /// without waiting for the result on the CPU. `mode` is e.g.
+    /// `gl::QUERY_WAIT` or `gl::QUERY_NO_WAIT`. Close with
+    /// `end_conditional_render`.
+    pub fn begin_conditional_render(&self, mode: GLenum) {
+        unsafe {
+            gl::BeginConditionalRender(self.id, mode);
+        }
+    }
+}
+
+/// Deletes the query.
+///
+/// Must be dropped on the thread that owns the GL context.
+impl Drop for OcclusionQuery {
+    fn drop(&mut self) {
+        if self.id != 0 {
+            unsafe {
+                gl::DeleteQueries(1, &self.id);
+            }
+        }
+    }
+}
+
+pub fn end_conditional_render() {
+    unsafe {
+        gl::EndConditionalRender();
+    }
+}
+
+/// Outcome of `FenceSync::wait`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SyncStatus {
+    /// The GPU has passed the fence, or no fence was set.
+    Signaled,
+    /// The timeout expired first.
+    TimedOut,
+    /// Waiting failed, e.g. because the context was lost.
+    Failed,
+}
+
+/// # Fence Sync
+/// Marks a point in the command stream so the CPU can find out when the GPU has
+/// got past it, e.g. before overwriting a region of a persistently mapped
+/// buffer that earlier draws read from.
+///
+/// ## Example
+/// ```ignore
+/// let mut fences: [FenceSync; 3] = Default::default();
+///
+/// let region = frame % 3;
+/// fences[region].wait(1_000_000_000);
+/// write_vertices(&mut mapped[region]);
+/// draw_region(region);
+/// fences[region].signal();
+/// ```
+pub struct FenceSync {
+    sync: GLsync,
+}
+
+impl FenceSync {
+    /// Create an unset fence. `wait` returns immediately until `signal` is
+    /// called.
+    pub fn new() -> FenceSync {
+        FenceSync { sync: ptr::null() }
+    }
+
+    /// Place the fence after all commands issued so far, replacing any earlier
+    /// one.
+    pub fn signal(&mut self) {
+        self.delete();
+        self.sync = unsafe { gl::FenceSync(gl::SYNC_GPU_COMMANDS_COMPLETE, 0) };
+    }
+
+    /// Block for up to `timeout_ns` nanoseconds until the GPU has passed the
+    /// fence. Pending commands are flushed first so the fence can be reached.
+    pub fn wait(&self, timeout_ns: u64) -> SyncStatus {
+        if self.sync.is_null() {
+            return SyncStatus::Signaled;
+        }
+
+        match unsafe { gl::ClientWaitSync(self.sync, gl::SYNC_FLUSH_COMMANDS_BIT, timeout_ns) } {
+            gl::ALREADY_SIGNALED | gl::CONDITION_SATISFIED => SyncStatus::Signaled,
+            gl::TIMEOUT_EXPIRED => SyncStatus::TimedOut,
+            _ => SyncStatus::Failed,
+        }
+    }
+
+    /// Whether the GPU has passed the fence, without blocking.
+    pub fn is_signaled(&self) -> bool {
+        if self.sync.is_null() {
+            return true;
+        }
+
+        let mut status = 0;
+        unsafe {
+            gl::GetSynciv(self.sync, gl::SYNC_STATUS, 1, ptr::null_mut(), &mut status);
+        }
+
+        status == gl::SIGNALED as GLint
+    }
+
+    fn delete(&mut self) {
+        if !self.sync.is_null() {
+            unsafe {
+                gl::DeleteSync(self.sync);
+            }
+            self.sync = ptr::null();
+        }
+    }
+}
+
+impl Default for FenceSync {
+    fn default() -> FenceSync {
+        FenceSync::new()
+    }
+}
+
+/// Deletes the sync object.
+///
+/// Must be dropped on the thread that owns the GL context.
+impl Drop for FenceSync {
+    fn drop(&mut self) {
+        self.delete();
+    }
+}
+
+/// # Transform Feedback
+/// Captures vertex or geometry shader outputs into buffers. The program must
+/// be built with `ShaderProgramBuilder::transform_feedback_varyings`.
+///
+/// ## Example
+/// ```ignore
+/// let feedback = TransformFeedback::new();
+/// feedback.bind();
+/// feedback.bind_buffer(0, &particles_out);
+///
+/// feedback.begin(gl::POINTS);
+/// draw_arrays(gl::POINTS, 0, particle_count);
+/// feedback.end();
+/// ```
+pub struct TransformFeedback {
+    id: GLuint,
+}
+
+impl TransformFeedback {
+    pub fn new() -> TransformFeedback {
+        let mut id = 0;
+        unsafe {
+            gl::GenTransformFeedbacks(1, &mut id);
+        }
+
+        TransformFeedback { id }
+    }
+
+    pub fn bind(&self) {
+        unsafe {
+            gl::BindTransformFeedback(gl::TRANSFORM_FEEDBACK, self.id);
+        }
+    }
+
+    pub fn unbind(&self) {
+        unsafe {
+            gl::BindTransformFeedback(gl::TRANSFORM_FEEDBACK, 0);
+        }
+    }
+
+    /// Capture output `index` into `buffer`. The transform feedback object has
+    /// to be bound.
+    pub fn bind_buffer(&self, index: u32, buffer: &BufferObject) {
+        unsafe {
+            gl::BindBufferBase(gl::TRANSFORM_FEEDBACK_BUFFER, index, buffer.id);
+        }
+    }
+
+    /// Start capturing. `primitive` must be `gl::POINTS`, `gl::LINES` or
+    /// `gl::TRIANGLES` and match the primitives being drawn.
+    pub fn begin(&self, primitive: GLenum) {
+        unsafe {
+            gl::BeginTransformFeedback(primitive);
+        }
+    }
+
+    pub fn end(&self) {
+        unsafe {
+            gl::EndTransformFeedback();
+        }
+        check_gl_error("TransformFeedback::end");
+    }
+}
+
+impl Default for TransformFeedback {
+    fn default() -> TransformFeedback {
+        TransformFeedback::new()
+    }
+}
+
+/// Deletes the transform feedback object.
+///
+/// Must be dropped on the thread that owns the GL context.
+impl Drop for TransformFeedback {
+    fn drop(&mut self) {
+        if self.id != 0 {
+            unsafe {
+                gl::DeleteTransformFeedbacks(1, &self.id);
+            }
+        }
+    }
+}
+
+/// # Shader Program
+/// Uniform locations are looked up and cached the first time they are set, so
+/// calling `create_uniform` beforehand is optional. Uniforms that don't exist in
+/// the program are silently skipped. Setters write to this program whether it
+/// is bound or not; before GL 4.1 they bind it briefly and restore the
+/// previously bound program.
+///
+/// ## Examples
+/// ```ignore
+/// let program = ShaderProgram::new("/path/to/vertexShader.glsl", "/path/to/fragmentShader.glsl");
+/// program.bind();
+///
+/// program.set_matrix4fv_uniform("transform", some_matrix);
+/// ```
+pub struct ShaderProgram {
+    program_handle: u32,
+    uniform_ids: RefCell<HashMap<String, GLint>>,
+    shader_paths: Option<(String, String)>,
+}
+
+impl ShaderProgram {
+    /// Create a new shader program, panicking if it cannot be built.
+    pub fn new(vertex_shader_path: &str, fragment_shader_path: &str) -> ShaderProgram {
+        ShaderProgram::try_new(vertex_shader_path, fragment_shader_path)
+            .unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Create a new shader program, returning an error instead of panicking.
+    pub fn try_new(
+        vertex_shader_path: &str,
+        fragment_shader_path: &str,
+    ) -> Result<ShaderProgram, ShaderError> {
+        let vertex_shader_source = load_shader_source(vertex_shader_path)?;
+        let fragment_shader_source = load_shader_source(fragment_shader_path)?;
+
+        let mut program = ShaderProgram::builder()
+            .vertex(&vertex_shader_source)
+            .fragment(&fragment_shader_source)
+            .build()?;
+        program.shader_paths = Some((
+            vertex_shader_path.to_string(),
+            fragment_shader_path.to_string(),
+        ));
+
+        Ok(program)
+    }
+
+    /// Recompile the program from the files it was created from.
+    ///
+    /// On success the old program is deleted and replaced, so it has to be bound
+    /// again before drawing. On failure the old program is kept and the error is
+    /// returned.
+    pub fn reload(&mut self) -> Result<(), ShaderError> {
+        let (vertex_shader_path, fragment_shader_path) = self
+            .shader_paths
+            .clone()
+            .ok_or(ShaderError::NotReloadable)?;
+
+        let mut reloaded = ShaderProgram::try_new(&vertex_shader_path, &fragment_shader_path)?;
+
+        // `reloaded` takes the old handle and deletes it when dropped.
+        mem::swap(&mut self.program_handle, &mut reloaded.program_handle);
+        self.uniform_ids.get_mut().clear();
+
+        Ok(())
+    }
+
+    /// Create a new shader program from in-memory GLSL sources. The sources are
+    /// compiled as they are, without touching the filesystem.
+    ///
+    /// ## Example
+    /// ```ignore
+    /// let program = ShaderProgram::from_source(
+    ///     include_str!("shaders/vertex.glsl"),
+    ///     include_str!("shaders/fragment.glsl"),
+    /// )?;
+    /// ```
+    pub fn from_source(
+        vertex_shader_source: &str,
+        fragment_shader_source: &str,
+    ) -> Result<ShaderProgram, ShaderError> {
+        ShaderProgram::builder()
+            .vertex(vertex_shader_source)
+            .fragment(fragment_shader_source)
+            .build()
+    }
+
+    /// Like `from_source`, but resolves `#include "path"` lines relative to
+    /// `include_directory`.
+    ///
+    /// ## Example
+    /// ```ignore
+    /// let program = ShaderProgram::from_source_with_includes(
+    ///     include_str!("shaders/lit.vert"),
+    ///     include_str!("shaders/lit.frag"),
+    ///     Path::new("shaders"),
+    /// )?;
+    /// ```
+    pub fn from_source_with_includes(
+        vertex_shader_source: &str,
+        fragment_shader_source: &str,
+        include_directory: &Path,
+    ) -> Result<ShaderProgram, ShaderError> {
+        let vertex_shader_source = resolve_includes(vertex_shader_source, include_directory)?;
+        let fragment_shader_source = resolve_includes(fragment_shader_source, include_directory)?;
+
+        ShaderProgram::from_source(&vertex_shader_source, &fragment_shader_source)
+    }
+
+    /// Like `from_source`, but with a `#define KEY VALUE` line for every entry in
+    /// `defines` inserted right after the `#version` directive of both stages.
+    ///
+    /// ## Example
+    /// ```ignore
+    /// let program = ShaderProgram::from_source_with_defines(
+    ///     include_str!("shaders/lit.vert"),
+    ///     include_str!("shaders/lit.frag"),
+    ///     &[("SHADOWS", "1"), ("FOG_DENSITY", "0.02")],
+    /// )?;
+    /// ```
+    pub fn from_source_with_defines(
+        vertex_shader_source: &str,
+        fragment_shader_source: &str,
+        defines: &[(&str, &str)],
+    ) -> Result<ShaderProgram, ShaderError> {
+        let vertex_shader_source = inject_defines(vertex_shader_source, defines);
+        let fragment_shader_source = inject_defines(fragment_shader_source, defines);
+
+        ShaderProgram::builder()
+            .vertex(&vertex_shader_source)
+            .fragment(&fragment_shader_source)
+            .build()
+    }
+
+    /// Save the linked program binary so `load_binary` can skip compilation on
+    /// the next run. The binary format token is stored in the first four bytes.
+    ///
+    /// Fails if the context lacks GL 4.1 and `GL_ARB_get_program_binary`, or if
+    /// the driver supports no binary formats.
+    pub fn save_binary(&self, path: &str) -> Result<(), ShaderError> {
+        require_program_binary()?;
+
+        let mut length = 0;
+        unsafe {
+            gl::GetProgramiv(self.program_handle, gl::PROGRAM_BINARY_LENGTH, &mut length);
+        }
+        if length <= 0 {
+            return Err(ShaderError::BinaryUnavailable);
+        }
+
+        let mut binary = vec![0u8; length.max(0) as usize];
+        let mut written = 0;
+        let mut format = 0;
+        unsafe {
+            gl::GetProgramBinary(
+                self.program_handle,
+                length,
+                &mut written,
+                &mut format,
+                binary.as_mut_ptr() as *mut c_void,
+            );
+        }
+        if written <= 0 {
+            return Err(ShaderError::BinaryUnavailable);
+        }
+        binary.truncate(written as usize);
+
+        let mut contents = format.to_le_bytes().to_vec();
+        contents.extend_from_slice(&binary);
+
+        std::fs::write(path, contents).map_err(|source| ShaderError::FileWrite {
+            path: path.to_string(),
+            source,
+        })
+    }
+
+    /// Load a program saved with `save_binary`.
+    ///
+    /// Drivers reject binaries from other GPUs or driver versions, so on any
+    /// error the caller should fall back to compiling from source.
+    pub fn load_binary(path: &str) -> Result<ShaderProgram, ShaderError> {
+        require_program_binary()?;
+
+        let contents = std::fs::read(path).map_err(|source| ShaderError::FileRead {
+            path: path.to_string(),
+            source,
+        })?;
+
+        if contents.len() < 4 {
+            return Err(ShaderError::InvalidBinary(path.to_string()));
+        }
+        let (format, binary) = contents.split_at(4);
+        let format = GLenum::from_le_bytes([format[0], format[1], format[2], format[3]]);
+
+        unsafe {
+            let program_handle = gl::CreateProgram();
+            gl::ProgramBinary(
+                program_handle,
+                format,
+                binary.as_ptr() as *const c_void,
+                binary.len() as GLsizei,
+            );
+
+            if let Err(log) = check_link(program_handle) {
+                gl::DeleteProgram(program_handle);
+                return Err(ShaderError::Link(log));
+            }
+
+            Ok(ShaderProgram {
+                program_handle,
+                uniform_ids: RefCell::new(HashMap::new()),
+                shader_paths: None,
+            })
+        }
+    }
+
+    /// Release ownership of the GL program without deleting it.
+    pub fn into_raw(mut self) -> GLuint {
+        mem::replace(&mut self.program_handle, 0)
+    }
+
+    /// Take ownership of an existing, linked program.
+    ///
+    /// # Safety
+    /// `program_handle` must be a program name that nothing else will delete.
+    pub unsafe fn from_raw(program_handle: GLuint) -> ShaderProgram {
+        ShaderProgram {
+            program_handle,
+            uniform_ids: RefCell::new(HashMap::new()),
+            shader_paths: None,
+        }
+    }
+
+    /// Start building a program from an arbitrary set of shader stages.
+    pub fn builder<'a>() -> ShaderProgramBuilder<'a> {
+        ShaderProgramBuilder::default()
+    }
+
+    /// Delete every compiled shader kept around for reuse.
+    ///
+    /// Programs sharing a shader source reuse the compiled shader object
+    /// instead of compiling it again. The cache lives until a new context is
+    /// loaded with `Window::init_gl` and only grows: `reload` adds the edited sources without dropping the old
+    /// ones. Existing programs are not affected by clearing the cache; call this
+    /// once loading is done, or after hot reloading, to free the memory.
+    pub fn clear_cache() {
+        SHADER_CACHE.with(|cache| {
+            for (_, shader) in cache.borrow_mut().drain() {
+                unsafe {
+                    gl::DeleteShader(shader);
+                }
+            }
+        });
+    }
+
+    pub fn bind(&self) {
+        unsafe {
+            gl::UseProgram(self.program_handle);
+        }
+    }
+
+    pub fn unbind() {
+        unsafe {
+            gl::UseProgram(0);
+        }
+    }
+
+    /// Name the program in debugging tools such as RenderDoc. Does nothing
+    /// without `KHR_debug`.
+    pub fn set_label(&self, label: &str) {
+        set_object_label(gl::PROGRAM, self.program_handle, label);
+    }
+
+    /// Look up and cache a uniform location.
+    ///
+    /// Uniforms that the driver optimized away are cached as missing, so
+    /// setting them later is a no-op rather than an error, and `None` is returned.
+    pub fn create_uniform(&mut self, uniform_name: &str) -> Option<GLint> {
+        self.uniform_location(uniform_name)
+    }
+
+    /// Whether the uniform exists in the linked program.
+    pub fn has_uniform(&self, uniform_name: &str) -> bool {
+        self.uniform_location(uniform_name).is_some()
+    }
+
+    /// Check whether the program can run with the current GL state, e.g. that
+    /// no two sampler types share a texture unit. Returns the info log if not.
+    ///
+    /// Validation is slow, so only call this while debugging, after the
+    /// textures and uniforms for a draw are set up.
+    pub fn validate(&self) -> Result<(), String> {
+        let mut status = gl::FALSE as GLint;
+        unsafe {
+            gl::ValidateProgram(self.program_handle);
+            gl::GetProgramiv(self.program_handle, gl::VALIDATE_STATUS, &mut status);
+        }
+
+        if status == gl::TRUE as GLint {
+            Ok(())
+        } else {
+            Err(program_info_log(self.program_handle))
+        }
+    }
+
+    /// Connect a uniform block to a binding point.
+    ///
+    /// Returns `false` if the program has no block with that name.
+    pub fn bind_uniform_block(&self, block_name: &str, binding_point: u32) -> bool {
+        let Ok(c_name) = CString::new(block_name) else {
+            return false;
+        };
+        let block_index = unsafe { gl::GetUniformBlockIndex(self.program_handle, c_name.as_ptr()) };
+        if block_index == gl::INVALID_INDEX {
+            return false;
+        }
+
+        unsafe {
+            gl::UniformBlockBinding(self.program_handle, block_index, binding_point);
+        }
+        true
+    }
+
+    /// Connect a shader storage block to a binding point.
+    ///
+    /// Returns `false` if the program has no block with that name.
+    pub fn bind_storage_block(&self, block_name: &str, binding_point: u32) -> bool {
+        let Ok(c_name) = CString::new(block_name) else {
+            return false;
+        };
+        let block_index = unsafe {
+            gl::GetProgramResourceIndex(
+                self.program_handle,
+                gl::SHADER_STORAGE_BLOCK,
+                c_name.as_ptr(),
+            )
+        };
+        if block_index == gl::INVALID_INDEX {
+            return false;
+        }
+
+        unsafe {
+            gl::ShaderStorageBlockBinding(self.program_handle, block_index, binding_point);
+        }
+        true
+    }
+
+    pub fn set_matrix4fv_uniform(&self, uniform_name: &str, matrix: &cgmath::Matrix4<f32>) {
+        self.set_named_uniform(uniform_name, matrix);
+    }
+
+    pub fn set_uniform_f32(&self, uniform_name: &str, value: f32) {
+        self.set_named_uniform(uniform_name, &value);
+    }
+
+    pub fn set_uniform_i32(&self, uniform_name: &str, value: i32) {
+        self.set_named_uniform(uniform_name, &value);
+    }
+
+    /// Set a `double` uniform. Fails if the context supports neither GL 4.0 nor
+    /// `GL_ARB_gpu_shader_fp64`.
+    pub fn set_uniform_f64(&self, uniform_name: &str, value: f64) -> Result<(), ShaderError> {
+        require_fp64()?;
+        if let Some(location) = self.uniform_location(uniform_name) {
+            let program = self.program_handle;
+            with_uniform_program(program, |direct| unsafe {
+                if direct {
+                    gl::ProgramUniform1d(program, location, value)
+                } else {
+                    gl::Uniform1d(location, value)
+                }
+            });
+        }
+        Ok(())
+    }
+
+    /// Set a `dvec3` uniform. See `set_uniform_f64`.
+    pub fn set_uniform_dvec3(
+        &self,
+        uniform_name: &str,
+        vector: &cgmath::Vector3<f64>,
+    ) -> Result<(), ShaderError> {
+        require_fp64()?;
+        if let Some(location) = self.uniform_location(uniform_name) {
+            let program = self.program_handle;
+            with_uniform_program(program, |direct| unsafe {
+                if direct {
+                    gl::ProgramUniform3dv(program, location, 1, vector.as_ptr())
+                } else {
+                    gl::Uniform3dv(location, 1, vector.as_ptr())
+                }
+            });
+        }
+        Ok(())
+    }
+
+    pub fn set_uniform_vec2(&self, uniform_name: &str, vector: &cgmath::Vector2<f32>) {
+        self.set_named_uniform(uniform_name, vector);
+    }
+
+    pub fn set_uniform_vec3(&self, uniform_name: &str, vector: &cgmath::Vector3<f32>) {
+        self.set_named_uniform(uniform_name, vector);
+    }
+
+    pub fn set_uniform_vec4(&self, uniform_name: &str, vector: &cgmath::Vector4<f32>) {
+        self.set_named_uniform(uniform_name, vector);
+    }
+
+    pub fn set_uniform_mat3(&self, uniform_name: &str, matrix: &cgmath::Matrix3<f32>) {
+        self.set_named_uniform(uniform_name, matrix);
+    }
+
+    /// Set a `vec4` uniform from a `Color`.
+    pub fn set_uniform_color(&self, uniform_name: &str, color: &Color) {
+        self.set_named_uniform(uniform_name, color);
+    }
+
+    /// Set a `mat4` array uniform such as `uniform mat4 bones[64]`.
+    pub fn set_matrix4fv_array(&self, uniform_name: &str, matrices: &[cgmath::Matrix4<f32>]) {
+        // `Matrix4` is `repr(C)` with no padding, so the slice is one contiguous run of floats.
+        const _: () = assert!(mem::size_of::<cgmath::Matrix4<f32>>() == 16 * mem::size_of::<f32>());
+
+        if matrices.is_empty() {
+            return;
+        }
+
+        if let Some(location) = self.uniform_location(uniform_name) {
+            let program = self.program_handle;
+            let count = matrices.len() as GLsizei;
+            let data = matrices.as_ptr() as *const f32;
+            with_uniform_program(program, |direct| unsafe {
+                if direct {
+                    gl::ProgramUniformMatrix4fv(program, location, count, gl::FALSE, data)
+                } else {
+                    gl::UniformMatrix4fv(location, count, gl::FALSE, data)
+                }
+            });
+        }
+    }
+
+    fn set_named_uniform<T: UniformValue>(&self, uniform_name: &str, value: &T) {
+        if let Some(location) = self.uniform_location(uniform_name) {
+            value.set_uniform(self.program_handle, location);
+        }
+    }
+
+    /// Look up a uniform location, querying and caching it on first use.
+    ///
+    /// Returns `None` if the uniform does not exist in the program, e.g.
+    /// because the driver optimized it away, or if the name contains a NUL byte.
+    pub fn uniform_location(&self, uniform_name: &str) -> Option<GLint> {
+        let cached = self.uniform_ids.borrow().get(uniform_name).copied();
+        let location = match cached {
+            Some(location) => location,
+            None => {
+                // No uniform can have a name with a NUL byte, so cache it as missing.
+                let location = match CString::new(uniform_name) {
+                    Ok(c_name) => unsafe {
+                        gl::GetUniformLocation(self.program_handle, c_name.as_ptr())
+                    },
+                    Err(_) => -1,
+                };
+                self.uniform_ids
+                    .borrow_mut()
+                    .insert(uniform_name.to_string(), location);
+                location
+            }
+        };
+
+        (location >= 0).then_some(location)
+    }
+
+    /// Look up a uniform once and get a typed handle for `set_uniform`, which
+    /// skips the name lookup. Returns `None` if the uniform does not exist.
+    ///
+    /// Handles are only valid for this program and until it is reloaded. Debug
+    /// builds panic when a handle is passed to a different program.
+    ///
+    /// ## Example
+    /// ```ignore
+    /// let transform = program.uniform::<Matrix4<f32>>("transform").unwrap();
+    ///
+    /// for object in &objects {
+    ///     program.set_uniform(transform, &object.matrix());
+    ///     object.draw();
+    /// }
+    /// ```
+    pub fn uniform<T: UniformValue>(&self, uniform_name: &str) -> Option<UniformHandle<T>> {
+        self.uniform_location(uniform_name)
+            .map(|location| UniformHandle {
+                program: self.program_handle,
+                location,
+                value_type: PhantomData,
+            })
+    }
+
+    pub fn set_uniform<T: UniformValue>(&self, handle: UniformHandle<T>, value: &T) {
+        debug_assert_eq!(
+            handle.program, self.program_handle,
+            "uniform handle belongs to a different program"
+        );
+        value.set_uniform(self.program_handle, handle.location);
+    }
+
+    /// Every active uniform in the program, including ones inside uniform
+    /// blocks. Arrays are listed once, named like `"lights[0]"`.
+    ///
+    /// ## Example
+    /// ```ignore
+    /// for uniform in program.list_uniforms() {
+    ///     if uniform.gl_type == gl::FLOAT {
+    ///         editor.add_slider(&uniform.name);
+    ///     }
+    /// }
+    /// ```
+    pub fn list_uniforms(&self) -> Vec<UniformInfo> {
+        let (mut count, mut max_name_length) = (0, 0);
+        unsafe {
+            gl::GetProgramiv(self.program_handle, gl::ACTIVE_UNIFORMS, &mut count);
+            gl::GetProgramiv(
+                self.program_handle,
+                gl::ACTIVE_UNIFORM_MAX_LENGTH,
+                &mut max_name_length,
+            );
+        }
+
+        let mut name = vec![0u8; max_name_length.max(1) as usize];
+        (0..count.max(0) as GLuint)
+            .map(|index| {
+                let (mut written, mut size, mut gl_type) = (0, 0, 0);
+                unsafe {
+                    gl::GetActiveUniform(
+                        self.program_handle,
+                        index,
+                        name.len() as GLsizei,
+                        &mut written,
+                        &mut size,
+                        &mut gl_type,
+                        name.as_mut_ptr() as *mut GLchar,
+                    );
+                }
+
+                let name = String::from_utf8_lossy(&name[..written.max(0) as usize]).into_owned();
+                let location = self.uniform_location(&name).unwrap_or(-1);
+                UniformInfo {
+                    name,
+                    gl_type,
+                    size,
+                    location,
+                }
+            })
+            .collect()
+    }
+}
+
+/// An active uniform reported by `ShaderProgram::list_uniforms`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UniformInfo {
+    pub name: String,
+    /// The GLSL type, e.g. `gl::FLOAT_VEC3` or `gl::SAMPLER_2D`.
+    pub gl_type: GLenum,
+    /// The number of elements, 1 unless the uniform is an array.
+    pub size: i32,
+    /// -1 for uniforms inside a uniform block, which have no location.
+    pub location: GLint,
+}
+
+/// A cached uniform location that only accepts values of type `T`. See
+/// `ShaderProgram::uniform`.
+pub struct UniformHandle<T> {
+    /// The program the location was looked up in, checked by `set_uniform` in
+    /// debug builds.
+    program: GLuint,
+    location: GLint,
+    value_type: PhantomData<fn(&T)>,
+}
+
+impl<T> UniformHandle<T> {
+    pub fn location(&self) -> GLint {
+        self.location
+    }
+}
+
+impl<T> Clone for UniformHandle<T> {
+    fn clone(&self) -> UniformHandle<T> {
+        *self
+    }
+}
+
+impl<T> Copy for UniformHandle<T> {}
+
+/// Types that can be written to a uniform.
+pub trait UniformValue {
+    fn set_uniform(&self, program: GLuint, location: GLint);
+}
+
+impl UniformValue for f32 {
+    fn set_uniform(&self, program: GLuint, location: GLint) {
+        with_uniform_program(program, |direct| unsafe {
+            if direct {
+                gl::ProgramUniform1f(program, location, *self)
+            } else {
+                gl::Uniform1f(location, *self)
+            }
+        });
+    }
+}
+
+impl UniformValue for i32 {
+    fn set_uniform(&self, program: GLuint, location: GLint) {
+        with_uniform_program(program, |direct| unsafe {
+            if direct {
+                gl::ProgramUniform1i(program, location, *self)
+            } else {
+                gl::Uniform1i(location, *self)
+            }
+        });
+    }
+}
+
+impl UniformValue for u32 {
+    fn set_uniform(&self, program: GLuint, location: GLint) {
+        with_uniform_program(program, |direct| unsafe {
+            if direct {
+                gl::ProgramUniform1ui(program, location, *self)
+            } else {
+                gl::Uniform1ui(location, *self)
+            }
+        });
+    }
+}
+
+impl UniformValue for Vector2<f32> {
+    fn set_uniform(&self, program: GLuint, location: GLint) {
+        with_uniform_program(program, |direct| unsafe {
+            if direct {
+                gl::ProgramUniform2fv(program, location, 1, self.as_ptr())
+            } else {
+                gl::Uniform2fv(location, 1, self.as_ptr())
+            }
+        });
+    }
+}
+
+impl UniformValue for Vector3<f32> {
+    fn set_uniform(&self, program: GLuint, location: GLint) {
+        with_uniform_program(program, |direct| unsafe {
+            if direct {
+                gl::ProgramUniform3fv(program, location, 1, self.as_ptr())
+            } else {
+                gl::Uniform3fv(location, 1, self.as_ptr())
+            }
+        });
+    }
+}
+
+impl UniformValue for Vector4<f32> {
+    fn set_uniform(&self, program: GLuint, location: GLint) {
+        with_uniform_program(program, |direct| unsafe {
+            if direct {
+                gl::ProgramUniform4fv(program, location, 1, self.as_ptr())
+            } else {
+                gl::Uniform4fv(location, 1, self.as_ptr())
+            }
+        });
+    }
+}
+
+impl UniformValue for Matrix3<f32> {
+    fn set_uniform(&self, program: GLuint, location: GLint) {
+        with_uniform_program(program, |direct| unsafe {
+            if direct {
+                gl::ProgramUniformMatrix3fv(program, location, 1, gl::FALSE, self.as_ptr())
+            } else {
+                gl::UniformMatrix3fv(location, 1, gl::FALSE, self.as_ptr())
+            }
+        });
+    }
+}
+
+impl UniformValue for Matrix4<f32> {
+    fn set_uniform(&self, program: GLuint, location: GLint) {
+        with_uniform_program(program, |direct| unsafe {
+            if direct {
+                gl::ProgramUniformMatrix4fv(program, location, 1, gl::FALSE, self.as_ptr())
+            } else {
+                gl::UniformMatrix4fv(location, 1, gl::FALSE, self.as_ptr())
+            }
+        });
+    }
+}
+
+impl UniformValue for Color {
+    fn set_uniform(&self, program: GLuint, location: GLint) {
+        with_uniform_program(program, |direct| unsafe {
+            if direct {
+                gl::ProgramUniform4f(program, location, self.r, self.g, self.b, self.a)
+            } else {
+                gl::Uniform4f(location, self.r, self.g, self.b, self.a)
+            }
+        });
+    }
+}
+
+thread_local! {
+    static SUPPORTS_PROGRAM_UNIFORM: Cell<Option<bool>> = const { Cell::new(None) };
+}
+
+/// Whether `glProgramUniform*` is available (GL 4.1 or
+/// `GL_ARB_separate_shader_objects`). The answer is cached per thread until
+/// `Window::init_gl` loads a new context.
+fn supports_program_uniform() -> bool {
+    SUPPORTS_PROGRAM_UNIFORM.with(|cached| {
+        if let Some(supported) = cached.get() {
+            return supported;
+        }
+
+        let supported =
+            context_version() >= (4, 1) || has_extension("GL_ARB_separate_shader_objects");
+        cached.set(Some(supported));
+        supported
+    })
+}
+
+/// Run `set` to write a uniform of `program`. It is passed `true` if it can use
+/// `glProgramUniform*`. Otherwise it has to use `glUniform*`, and `program` is
+/// bound around the call, then the previously bound program is restored.
+fn with_uniform_program(program: GLuint, set: impl FnOnce(bool)) {
+    if supports_program_uniform() {
+        set(true);
+        return;
+    }
+
+    let previous = get_integer(gl::CURRENT_PROGRAM) as GLuint;
+    if previous == program {
+        set(false);
+        return;
+    }
+
+    unsafe {
+        gl::UseProgram(program);
+    }
+    set(false);
+    unsafe {
+        gl::UseProgram(previous);
+    }
+}
+
+/// # Shader Program Builder
+/// Compiles and links whichever shader stages are provided.
+///
+/// ## Example
+/// ```ignore
+/// let program = ShaderProgram::builder()
+///     .vertex(include_str!("shaders/billboard.vert"))
+///     .geometry(include_str!("shaders/billboard.geom"))
+///     .fragment(include_str!("shaders/billboard.frag"))
+///     .build()?;
+/// ```
+#[derive(Default)]
+pub struct ShaderProgramBuilder<'a> {
+    vertex: Option<&'a str>,
+    geometry: Option<&'a str>,
+    fragment: Option<&'a str>,
+    compute: Option<&'a str>,
+    feedback_varyings: Vec<&'a str>,
+    feedback_buffer_mode: GLenum,
+    frag_data_locations: Vec<(u32, &'a str)>,
+}
+
+impl<'a> ShaderProgramBuilder<'a> {
+    pub fn vertex(mut self, source: &'a str) -> ShaderProgramBuilder<'a> {
+        self.vertex = Some(source);
+        self
+    }
+
+    pub fn geometry(mut self, source: &'a str) -> ShaderProgramBuilder<'a> {
+        self.geometry = Some(source);
+        self
+    }
+
+    pub fn fragment(mut self, source: &'a str) -> ShaderProgramBuilder<'a> {
+        self.fragment = Some(source);
+        self
+    }
+
+    pub fn compute(mut self, source: &'a str) -> ShaderProgramBuilder<'a> {
+        self.compute = Some(source);
+        self
+    }
+
+    /// Capture the named vertex or geometry shader outputs with transform
+    /// feedback. `buffer_mode` is `gl::INTERLEAVED_ATTRIBS` to write them all to
+    /// one buffer or `gl::SEPARATE_ATTRIBS` for one buffer per output.
+    pub fn transform_feedback_varyings(
+        mut self,
+        varyings: &[&'a str],
+        buffer_mode: GLenum,
+    ) -> ShaderProgramBuilder<'a> {
+        self.feedback_varyings = varyings.to_vec();
+        self.feedback_buffer_mode = buffer_mode;
+        self
+    }
+
+    /// Write the fragment shader output `name` to draw buffer `color_number`,
+    /// for shaders without `layout(location = N)` on their outputs. Can be
+    /// called once per output.
+    ///
+    /// ## Example
+    /// ```ignore
+    /// let program = ShaderProgram::builder()
+    ///     .vertex(include_str!("shaders/g_buffer.vert"))
+    ///     .fragment(include_str!("shaders/g_buffer.frag"))
+    ///     .frag_data_location(0, "out_position")
+    ///     .frag_data_location(1, "out_normal")
+    ///     .frag_data_location(2, "out_albedo")
+    ///     .build()?;
+    /// ```
+    pub fn frag_data_location(
+        mut self,
+        color_number: u32,
+        name: &'a str,
+    ) -> ShaderProgramBuilder<'a> {
+        self.frag_data_locations.push((color_number, name));
+        self
+    }
+
+    pub fn build(self) -> Result<ShaderProgram, ShaderError> {
+        let stages = [
+            (self.vertex, gl::VERTEX_SHADER, "vertex"),
+            (self.geometry, gl::GEOMETRY_SHADER, "geometry"),
+            (self.fragment, gl::FRAGMENT_SHADER, "fragment"),
+            (self.compute, gl::COMPUTE_SHADER, "compute"),
+        ];
+
+        let mut shaders = Vec::new();
+        for (source, kind, stage) in stages {
+            let Some(source) = source else {
+                continue;
+            };
+
+            // Shaders that already compiled stay in the cache for the next attempt.
+            shaders.push(compile_shader_cached(source, kind, stage)?);
+        }
+
+        let frag_data_locations = self
+            .frag_data_locations
+            .iter()
+            .map(|&(color_number, name)| Ok((color_number, shader_name(name, "fragment")?)))
+            .collect::<Result<Vec<_>, ShaderError>>()?;
+        let feedback_stage = if self.geometry.is_some() {
+            "geometry"
+        } else {
+            "vertex"
+        };
+        let feedback_varyings = self
+            .feedback_varyings
+            .iter()
+            .map(|name| shader_name(name, feedback_stage))
+            .collect::<Result<Vec<_>, ShaderError>>()?;
+
+        let program_handle = link_program(&shaders, |program_handle| {
+            for (color_number, name) in &frag_data_locations {
+                unsafe {
+                    gl::BindFragDataLocation(program_handle, *color_number, name.as_ptr());
+                }
+            }
+
+            if feedback_varyings.is_empty() {
+                return;
+            }
+
+            let name_ptrs: Vec<*const GLchar> =
+                feedback_varyings.iter().map(|name| name.as_ptr()).collect();
+            unsafe {
+                gl::TransformFeedbackVaryings(
+                    program_handle,
+                    name_ptrs.len() as GLsizei,
+                    name_ptrs.as_ptr(),
+                    self.feedback_buffer_mode,
+                );
+            }
+        })?;
+
+        Ok(ShaderProgram {
+            program_handle,
+            uniform_ids: RefCell::new(HashMap::new()),
+            shader_paths: None,
+        })
+    }
+}
+
+/// # Compute Program
+/// A program made of a single compute shader. It dereferences to
+/// `ShaderProgram`, so uniforms are set the same way as for graphics programs.
+///
+/// ## Example
+/// ```ignore
+/// let simulation = ComputeProgram::new("/path/to/particles.comp");
+/// simulation.bind();
+/// simulation.set_uniform_f32("delta_time", dt);
+///
+/// simulation.dispatch(particle_count / 64, 1, 1);
+/// memory_barrier(gl::SHADER_STORAGE_BARRIER_BIT);
+/// ```
+pub struct ComputeProgram {
+    program: ShaderProgram,
+}
+
+impl ComputeProgram {
+    /// Create a new compute program, panicking if it cannot be built.
+    pub fn new(compute_shader_path: &str) -> ComputeProgram {
+        ComputeProgram::try_new(compute_shader_path).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Create a new compute program, returning an error instead of panicking.
+    pub fn try_new(compute_shader_path: &str) -> Result<ComputeProgram, ShaderError> {
+        let compute_shader_source = load_shader_source(compute_shader_path)?;
+        let program = ShaderProgram::builder()
+            .compute(&compute_shader_source)
+            .build()?;
+
+        Ok(ComputeProgram { program })
+    }
+
+    /// Create a new compute program from an in-memory GLSL source, compiled as
+    /// it is.
+    pub fn from_source(compute_shader_source: &str) -> Result<ComputeProgram, ShaderError> {
+        let program = ShaderProgram::builder()
+            .compute(compute_shader_source)
+            .build()?;
+
+        Ok(ComputeProgram { program })
+    }
+
+    /// Like `from_source`, but resolves `#include "path"` lines relative to
+    /// `include_directory`.
+    pub fn from_source_with_includes(
+        compute_shader_source: &str,
+        include_directory: &Path,
+    ) -> Result<ComputeProgram, ShaderError> {
+        let compute_shader_source = resolve_includes(compute_shader_source, include_directory)?;
+        ComputeProgram::from_source(&compute_shader_source)
+    }
+
+    /// Run the compute shader over the given number of work groups.
+    /// The program has to be bound.
+    pub fn dispatch(&self, groups_x: u32, groups_y: u32, groups_z: u32) {
+        unsafe {
+            gl::DispatchCompute(groups_x, groups_y, groups_z);
+        }
+        check_gl_error("ComputeProgram::dispatch");
+    }
+}
+
+impl Deref for ComputeProgram {
+    type Target = ShaderProgram;
+
+    fn deref(&self) -> &ShaderProgram {
+        &self.program
+    }
+}
+
+/// Deletes the shader program.
+///
+/// Dropping a program that is still bound is safe: GL flags it for deletion
+/// and frees it once it is no longer in use. Must be dropped on the thread
+/// that owns the GL context.
+impl Drop for ShaderProgram {
+    fn drop(&mut self) {
+        self.uniform_ids.get_mut().clear();
+        if self.program_handle != 0 {
+            unsafe {
+                gl::DeleteProgram(self.program_handle);
+            }
+        }
+    }
+}
+
+/// Label a GL object if `KHR_debug` is available, otherwise do nothing.
+///
+/// GL only creates an object on its first bind, so labelling a name from
+/// `glGen*` that was never bound fails with `GL_INVALID_VALUE`. Names from
+/// `glCreate*` can be labelled right away.
+fn set_object_label(identifier: GLenum, name: GLuint, label: &str) {
+    if !supports_khr_debug() {
+        return;
+    }
+
+    unsafe {
+        gl::ObjectLabel(
+            identifier,
+            name,
+            label.len() as GLsizei,
+            label.as_ptr() as *const GLchar,
+        );
+    }
+}
+
+/// Whether `KHR_debug` (core in GL 4.3) is available. Checking whether its
+/// functions are loaded isn't enough: GLX returns a pointer for any name.
+fn supports_khr_debug() -> bool {
+    context_version() >= (4, 3) || has_extension("GL_KHR_debug")
+}
+
+/// GL state set through the wrappers, used to skip redundant calls.
+#[derive(Default)]
+struct StateCache {
+    /// `None` when unknown, e.g. after `reset_state_cache`.
+    bound_vao: Option<GLuint>,
+    /// `(vao, attribute index)` pairs known to be enabled.
+    enabled_attributes: HashSet<(GLuint, GLuint)>,
+}
+
+thread_local! {
+    static STATE_CACHE: RefCell<StateCache> = RefCell::new(StateCache::default());
+}
+
+/// Forget the GL state the wrappers have tracked. Call this after code outside
+/// the wrappers, such as another library or raw `gl::` calls, binds VAOs or
+/// enables vertex attributes, so no call is skipped wrongly.
+pub fn reset_state_cache() {
+    STATE_CACHE.with(|cache| *cache.borrow_mut() = StateCache::default());
+}
+
+/// Drop the attributes cached as enabled on `vao`.
+fn forget_enabled_attributes(vao: GLuint) {
+    STATE_CACHE.with(|cache| {
+        cache
+            .borrow_mut()
+            .enabled_attributes
+            .retain(|&(cached_vao, _)| cached_vao != vao)
+    });
+}
+
+fn enable_vertex_attrib_array(index: GLuint) {
+    let skip = STATE_CACHE.with(|cache| {
+        let mut cache = cache.borrow_mut();
+        match cache.bound_vao {
+            Some(vao) => !cache.enabled_attributes.insert((vao, index)),
+            None => false,
+        }
+    });
+
+    if !skip {
+        unsafe {
+            gl::EnableVertexAttribArray(index);
+        }
+    }
+}
+
+thread_local! {
+    /// Compiled shader objects keyed by stage and full source text. GL objects
+    /// belong to the context's thread, so the cache is per thread, and
+    /// `forget_context` empties it when `Window::init_gl` loads a new context.
+    /// Otherwise entries are never evicted, not even by `ShaderProgram::reload`,
+    /// so every source ever compiled keeps its shader object alive until
+    /// `ShaderProgram::clear_cache`.
+    static SHADER_CACHE: RefCell<HashMap<(GLenum, String), GLuint>> = RefCell::new(HashMap::new());
+}
+
+/// Forget everything cached about the previous context. Called by
+/// `Window::init_gl`, since a context created later on the same thread would
+/// otherwise be handed shader names that only existed in the old one, and
+/// capabilities and bindings it may not have. The old shader names are not
+/// deleted, as they may belong to a context that is still alive.
+pub(crate) fn forget_context() {
+    SHADER_CACHE.with(|cache| cache.borrow_mut().clear());
+    SUPPORTS_PROGRAM_UNIFORM.with(|cached| cached.set(None));
+    SUPPORTS_DSA.with(|cached| cached.set(None));
+    EXTENSIONS.with(|cached| *cached.borrow_mut() = None);
+    reset_state_cache();
+}
+
+/// Compile a shader, reusing an earlier compile of the same source if possible.
+fn compile_shader_cached(source: &str, kind: GLenum, stage: &str) -> Result<GLuint, ShaderError> {
+    let key = (kind, source.to_string());
+
+    if let Some(shader) = SHADER_CACHE.with(|cache| cache.borrow().get(&key).copied()) {
+        return Ok(shader);
+    }
+
+    let shader = compile_shader(source, kind, stage)?;
+    SHADER_CACHE.with(|cache| cache.borrow_mut().insert(key, shader));
+
+    Ok(shader)
+}
+
+fn compile_shader(source: &str, kind: GLenum, stage: &str) -> Result<GLuint, ShaderError> {
+    let c_str = CString::new(source.as_bytes()).map_err(|e| ShaderError::InvalidSource {
+        stage: stage.to_string(),
+        position: e.nul_position(),
+    })?;
+
+    // Both the CString and the pointer array handed to GL must outlive the call.
+    let source_ptr = c_str.as_ptr();
+
+    unsafe {
+        let shader = gl::CreateShader(kind);
+        gl::ShaderSource(shader, 1, &source_ptr, ptr::null());
+        gl::CompileShader(shader);
+
+        if let Err(log) = check_compile(shader, stage) {
+            gl::DeleteShader(shader);
+            return Err(ShaderError::Compile {
+                stage: stage.to_string(),
+                log,
+            });
+        }
+
+        Ok(shader)
+    }
+}
+
+/// Convert the name of a variable declared in `stage`, failing on a NUL byte.
+fn shader_name(name: &str, stage: &str) -> Result<CString, ShaderError> {
+    CString::new(name).map_err(|e| ShaderError::InvalidSource {
+        stage: stage.to_string(),
+        position: e.nul_position(),
+    })
+}
+
+/// Query the compile status of a shader and return its info log on failure.
+fn check_compile(shader: GLuint, stage: &str) -> Result<(), String> {
+    let mut status = gl::FALSE as GLint;
+    unsafe {
+        gl::GetShaderiv(shader, gl::COMPILE_STATUS, &mut status);
+    }
+
+    if status == gl::TRUE as GLint {
+        return Ok(());
+    }
+
+    let mut log_length = 0;
+    unsafe {
+        gl::GetShaderiv(shader, gl::INFO_LOG_LENGTH, &mut log_length);
+    }
+
+    let mut log = vec![0u8; log_length.max(1) as usize];
+    let mut written = 0;
+    unsafe {
+        gl::GetShaderInfoLog(
+            shader,
+            log.len() as GLsizei,
+            &mut written,
+            log.as_mut_ptr() as *mut GLchar,
+        );
+    }
+    log.truncate(written as usize);
+
+    Err(format!(
+        "Failed to compile {} shader:\n{}",
+        stage,
+        String::from_utf8_lossy(&log)
+    ))
+}
+
+/// Link the given shaders into a new program.
+///
+/// `before_link` can set state that has to be in place when linking. The shaders
+/// are detached afterwards but stay alive in the shader cache. The program is
+/// deleted if linking fails.
+fn link_program(
+    shaders: &[GLuint],
+    before_link: impl FnOnce(GLuint),
+) -> Result<GLuint, ShaderError> {
+    unsafe {
+        let program_handle = gl::CreateProgram();
+        for &shader in shaders {
+            gl::AttachShader(program_handle, shader);
+        }
+        before_link(program_handle);
+        if supports_program_binary() {
+            // Lets `save_binary` retrieve the binary on drivers that only keep
+            // it when asked before linking.
+            gl::ProgramParameteri(
+                program_handle,
+                gl::PROGRAM_BINARY_RETRIEVABLE_HINT,
+                gl::TRUE as GLint,
+            );
+        }
+        gl::LinkProgram(program_handle);
+        for &shader in shaders {
+            gl::DetachShader(program_handle, shader);
+        }
+
+        if let Err(log) = check_link(program_handle) {
+            gl::DeleteProgram(program_handle);
+            return Err(ShaderError::Link(log));
+        }
+        check_gl_error("link_program");
+
+        Ok(program_handle)
+    }
+}
+
+/// Query the link status of a program and return its info log on failure.
+fn check_link(program: GLuint) -> Result<(), String> {
+    let mut status = gl::FALSE as GLint;
+    unsafe {
+        gl::GetProgramiv(program, gl::LINK_STATUS, &mut status);
+    }
+
+    if status == gl::TRUE as GLint {
+        return Ok(());
+    }
+
+    Err(format!(
+        "Failed to link shader program:\n{}",
+        program_info_log(program)
+    ))
+}
+
+fn supports_program_binary() -> bool {
+    context_version() >= (4, 1) || has_extension("GL_ARB_get_program_binary")
+}
+
+fn require_program_binary() -> Result<(), ShaderError> {
+    if supports_program_binary() {
+        Ok(())
+    } else {
+        Err(ShaderError::Unsupported("GL_ARB_get_program_binary"))
+    }
+}
+
+fn require_fp64() -> Result<(), ShaderError> {
+    if context_version() >= (4, 0) || has_extension("GL_ARB_gpu_shader_fp64") {
+        Ok(())
+    } else {
+        Err(ShaderError::Unsupported("GL_ARB_gpu_shader_fp64"))
+    }
+}
+
+fn program_info_log(program: GLuint) -> String {
+    let mut log_length = 0;
+    unsafe {
+        gl::GetProgramiv(program, gl::INFO_LOG_LENGTH, &mut log_length);
+    }
+
+    let mut log = vec![0u8; log_length.max(1) as usize];
+    let mut written = 0;
+    unsafe {
+        gl::GetProgramInfoLog(
+            program,
+            log.len() as GLsizei,
+            &mut written,
+            log.as_mut_ptr() as *mut GLchar,
+        );
+    }
+    log.truncate(written as usize);
+
+    String::from_utf8_lossy(&log).into_owned()
+}
+
+fn read_shader_file(path: &str) -> Result<String, ShaderError> {
+    let mut file = File::open(path).map_err(|source| ShaderError::FileOpen {
+        path: path.to_string(),
+        source,
+    })?;
+
+    let mut source = String::new();
+    file.read_to_string(&mut source)
+        .map_err(|source| ShaderError::FileRead {
+            path: path.to_string(),
+            source,
+        })?;
+
+    Ok(source)
+}
+
+/// Read a shader file and resolve its `#include` directives relative to the
+/// file's directory.
+fn load_shader_source(path: &str) -> Result<String, ShaderError> {
+    let source = read_shader_file(path)?;
+    let directory = Path::new(path).parent().unwrap_or_else(|| Path::new(""));
+    let mut include_stack = vec![canonicalize_shader_path(Path::new(path))?];
+
+    let mut output = String::new();
+    expand_includes(
+        &source,
+        directory,
+        0,
+        &mut 0,
+        &mut include_stack,
+        &mut output,
+    )?;
+    Ok(output)
+}
+
+/// Replace every `#include "path"` line in `source` with the contents of that
+/// file, resolved relative to `directory`. Included files may include others.
+///
+/// Each file gets its own GLSL source string number, counted up from 0 for
+/// `source` in the order the files are included, and `#line` directives are
+/// inserted so compiler errors report the line within that file.
+fn resolve_includes(source: &str, directory: &Path) -> Result<String, ShaderError> {
+    let mut output = String::new();
+    expand_includes(source, directory, 0, &mut 0, &mut Vec::new(), &mut output)?;
+    Ok(output)
+}
+
+fn expand_includes(
+    source: &str,
+    directory: &Path,
+    source_number: u32,
+    last_source_number: &mut u32,
+    include_stack: &mut Vec<PathBuf>,
+    output: &mut String,
+) -> Result<(), ShaderError> {
+    for (line_index, line) in source.lines().enumerate() {
+        let directive = match line.trim_start().strip_prefix("#include") {
+            Some(directive) => directive,
+            None => {
+                output.push_str(line);
+                output.push('\n');
+                continue;
+            }
+        };
+
+        let include = directive
+            .trim()
+            .strip_prefix('"')
+            .and_then(|include| include.strip_suffix('"'))
+            .ok_or_else(|| ShaderError::InvalidInclude(line.trim().to_string()))?;
+        let path = directory.join(include);
+        let canonical_path = canonicalize_shader_path(&path)?;
+        if include_stack.contains(&canonical_path) {
+            return Err(ShaderError::IncludeCycle(path.display().to_string()));
+        }
+
+        let included_source = read_shader_file(&path.to_string_lossy())?;
+        *last_source_number += 1;
+        let included_number = *last_source_number;
+
+        output.push_str(&format!("#line 1 {}\n", included_number));
+        include_stack.push(canonical_path);
+        expand_includes(
+            &included_source,
+            path.parent().unwrap_or(directory),
+            included_number,
+            last_source_number,
+            include_stack,
+            output,
+        )?;
+        include_stack.pop();
+        output.push_str(&format!("#line {} {}\n", line_index + 2, source_number));
+    }
+
+    Ok(())
+}
+
+/// Insert `#define` lines after the `#version` directive, or at the top if there
+/// is none, followed by a `#line` directive so line numbers in compiler errors
+/// are unchanged.
+fn inject_defines(source: &str, defines: &[(&str, &str)]) -> String {
+    if defines.is_empty() {
+        return source.to_string();
+    }
+
+    let lines: Vec<&str> = source.lines().collect();
+    let split = lines
+        .iter()
+        .position(|line| line.trim_start().starts_with("#version"))
+        .map_or(0, |version_line| version_line + 1);
+
+    let mut output = String::with_capacity(source.len());
+    for line in &lines[..split] {
+        output.push_str(line);
+        output.push('\n');
+    }
+    for (key, value) in defines {
+        output.push_str(&format!("#define {} {}\n", key, value));
+    }
+    output.push_str(&format!("#line {}\n", split + 1));
+    for line in &lines[split..] {
+        output.push_str(line);
+        output.push('\n');
+    }
+
+    output
+}
+
+fn canonicalize_shader_path(path: &Path) -> Result<PathBuf, ShaderError> {
+    path.canonicalize().map_err(|source| ShaderError::FileOpen {
+        path: path.display().to_string(),
+        source,
+    })
+}
+
+/// Make writes from earlier shader invocations visible to the operations in
+/// `barriers`, e.g. `gl::SHADER_STORAGE_BARRIER_BIT` before reading a buffer a
+/// compute shader wrote to.
+pub fn memory_barrier(barriers: GLbitfield) {
+    unsafe {
+        gl::MemoryBarrier(barriers);
+    }
+}
+
+/// Draw `count` vertices from the bound VAO, starting at `first`.
+///
+/// ## Example
+/// ```ignore
+/// program.bind();
+/// vao.bind();
+/// draw_arrays(gl::TRIANGLES, 0, 3);
+/// ```
+pub fn draw_arrays(mode: GLenum, first: i32, count: i32) {
+    unsafe {
+        gl::DrawArrays(mode, first, count);
+    }
+    check_gl_error("draw_arrays");
+}
+
+/// Draw indexed geometry from the bound element buffer.
+///
+/// The VAO must be bound and an `ELEMENT_ARRAY_BUFFER` must have been bound
+/// while that VAO was active. `index_type` has to match the store function used
+/// for the indices, e.g. `gl::UNSIGNED_INT` for `store_u32_data`.
+///
+/// ## Example
+/// ```ignore
+/// vao.bind();
+/// let ebo = BufferObject::element_buffer();
+/// ebo.bind();
+/// ebo.store_u32_data(&indices);
+///
+/// draw_elements(gl::TRIANGLES, indices.len() as i32, gl::UNSIGNED_INT);
+/// ```
+pub fn draw_elements(mode: GLenum, count: i32, index_type: GLenum) {
+    unsafe {
+        gl::DrawElements(mode, count, index_type, ptr::null());
+    }
+    check_gl_error("draw_elements");
+}
+
+/// Draw `instance_count` instances of the vertices in the bound VAO.
+pub fn draw_arrays_instanced(mode: GLenum, first: i32, count: i32, instance_count: i32) {
+    unsafe {
+        gl::DrawArraysInstanced(mode, first, count, instance_count);
+    }
+    check_gl_error("draw_arrays_instanced");
+}
+
+pub fn set_viewport(x: i32, y: i32, width: i32, height: i32) {
+    unsafe {
+        gl::Viewport(x, y, width, height);
+    }
+}
+
+pub fn set_clear_color(r: f32, g: f32, b: f32, a: f32) {
+    unsafe {
+        gl::ClearColor(r, g, b, a);
+    }
+}
+
+/// Clear the buffers in `mask`, e.g. `gl::COLOR_BUFFER_BIT | gl::DEPTH_BUFFER_BIT`.
+pub fn clear(mask: GLbitfield) {
+    unsafe {
+        gl::Clear(mask);
+    }
+}
+
+/// Clear color attachment `index` of the bound draw framebuffer to `color`,
+/// leaving the other attachments untouched. `index` is the position in the
+/// framebuffer's draw buffer list, not the attachment number.
+pub fn clear_color_attachment(index: i32, color: [f32; 4]) {
+    unsafe {
+        gl::ClearBufferfv(gl::COLOR, index, color.as_ptr());
+    }
+}
+
+/// Clear only the depth buffer of the bound draw framebuffer to `value`.
+pub fn clear_depth(value: f32) {
+    unsafe {
+        gl::ClearBufferfv(gl::DEPTH, 0, &value);
+    }
+}
+
+pub fn enable_depth_test() {
+    unsafe {
+        gl::Enable(gl::DEPTH_TEST);
+    }
+}
+
+pub fn disable_depth_test() {
+    unsafe {
+        gl::Disable(gl::DEPTH_TEST);
+    }
+}
+
+/// Set the depth comparison, e.g. `gl::LEQUAL` for skyboxes drawn at the far plane.
+pub fn set_depth_func(func: GLenum) {
+    unsafe {
+        gl::DepthFunc(func);
+    }
+}
+
+pub fn enable_blend() {
+    unsafe {
+        gl::Enable(gl::BLEND);
+    }
+}
+
+pub fn disable_blend() {
+    unsafe {
+        gl::Disable(gl::BLEND);
+    }
+}
+
+/// Set the blend factors. Regular alpha transparency is
+/// `set_blend_func(gl::SRC_ALPHA, gl::ONE_MINUS_SRC_ALPHA)`.
+pub fn set_blend_func(src: GLenum, dst: GLenum) {
+    unsafe {
+        gl::BlendFunc(src, dst);
+    }
+}
+
+pub fn set_polygon_mode(face: GLenum, mode: GLenum) {
+    unsafe {
+        gl::PolygonMode(face, mode);
+    }
+}
+
+/// Draw polygons as outlines, useful for inspecting mesh topology.
+pub fn enable_wireframe() {
+    set_polygon_mode(gl::FRONT_AND_BACK, gl::LINE);
+}
+
+pub fn disable_wireframe() {
+    set_polygon_mode(gl::FRONT_AND_BACK, gl::FILL);
+}
+
+/// Set the width of rasterized lines, clamped to `GL_ALIASED_LINE_WIDTH_RANGE`.
+///
+/// Core profiles only guarantee a width of 1.0; many drivers clamp anything
+/// wider back to 1.0, so don't rely on it for more than debug drawing.
+pub fn set_line_width(width: f32) {
+    let mut range = [1.0f32; 2];
+    unsafe {
+        gl::GetFloatv(gl::ALIASED_LINE_WIDTH_RANGE, range.as_mut_ptr());
+        gl::LineWidth(width.clamp(range[0], range[1].max(range[0])));
+    }
+}
+
+/// Let vertex shaders set the point size through `gl_PointSize`.
+pub fn enable_program_point_size() {
+    unsafe {
+        gl::Enable(gl::PROGRAM_POINT_SIZE);
+    }
+}
+
+pub fn disable_program_point_size() {
+    unsafe {
+        gl::Disable(gl::PROGRAM_POINT_SIZE);
+    }
+}
+
+/// Set the point size used while program point size is disabled.
+pub fn set_point_size(size: f32) {
+    unsafe {
+        gl::PointSize(size);
+    }
+}
+
+/// Convert linear shader output to sRGB when writing to sRGB framebuffers,
+/// including the default framebuffer if it was created sRGB capable. Combine
+/// it with sRGB textures for gamma-correct rendering.
+pub fn enable_framebuffer_srgb() {
+    unsafe {
+        gl::Enable(gl::FRAMEBUFFER_SRGB);
+    }
+}
+
+pub fn disable_framebuffer_srgb() {
+    unsafe {
+        gl::Disable(gl::FRAMEBUFFER_SRGB);
+    }
+}
+
+/// Restart the primitive whenever `index` appears in the index buffer, so many
+/// triangle strips or line strips can be drawn with one call.
+///
+/// The restart index is conventionally the largest value of the index type,
+/// `u16::MAX` or `u32::MAX`, since no real vertex uses it.
+pub fn enable_primitive_restart(index: u32) {
+    unsafe {
+        gl::Enable(gl::PRIMITIVE_RESTART);
+        gl::PrimitiveRestartIndex(index);
+    }
+}
+
+pub fn disable_primitive_restart() {
+    unsafe {
+        gl::Disable(gl::PRIMITIVE_RESTART);
+    }
+}
+
+pub fn enable_cull_face() {
+    unsafe {
+        gl::Enable(gl::CULL_FACE);
+    }
+}
+
+pub fn disable_cull_face() {
+    unsafe {
+        gl::Disable(gl::CULL_FACE);
+    }
+}
+
+/// Choose which faces are culled: `gl::BACK` (the default), `gl::FRONT` or `gl::FRONT_AND_BACK`.
+pub fn set_cull_face(mode: GLenum) {
+    unsafe {
+        gl::CullFace(mode);
+    }
+}
+
+/// Choose the winding of front faces: `gl::CCW` (the default) or `gl::CW`.
+pub fn set_front_face(winding: GLenum) {
+    unsafe {
+        gl::FrontFace(winding);
+    }
+}
+
+pub fn enable_scissor_test() {
+    unsafe {
+        gl::Enable(gl::SCISSOR_TEST);
+    }
+}
+
+pub fn disable_scissor_test() {
+    unsafe {
+        gl::Disable(gl::SCISSOR_TEST);
+    }
+}
+
+/// Restrict drawing and clearing to a rectangle. Coordinates are in pixels
+/// with the origin at the bottom-left of the framebuffer.
+pub fn set_scissor(x: i32, y: i32, width: i32, height: i32) {
+    unsafe {
+        gl::Scissor(x, y, width, height);
+    }
+}
+
+pub fn enable_stencil_test() {
+    unsafe {
+        gl::Enable(gl::STENCIL_TEST);
+    }
+}
+
+pub fn disable_stencil_test() {
+    unsafe {
+        gl::Disable(gl::STENCIL_TEST);
+    }
+}
+
+/// Pass the stencil test when `func` holds between `reference & mask` and the
+/// stored value `& mask`.
+pub fn set_stencil_func(func: GLenum, reference: i32, mask: u32) {
+    unsafe {
+        gl::StencilFunc(func, reference, mask);
+    }
+}
+
+/// Choose what happens to the stored value when the stencil test fails, when
+/// it passes but the depth test fails, and when both pass.
+pub fn set_stencil_op(stencil_fail: GLenum, depth_fail: GLenum, depth_pass: GLenum) {
+    unsafe {
+        gl::StencilOp(stencil_fail, depth_fail, depth_pass);
+    }
+}
+
+/// Choose which stencil bits can be written. `0x00` makes the buffer read-only.
+pub fn set_stencil_mask(mask: u32) {
+    unsafe {
+        gl::StencilMask(mask);
+    }
+}
+
+/// Clear the color, depth and stencil buffers.
+pub fn clear_all() {
+    clear(gl::COLOR_BUFFER_BIT | gl::DEPTH_BUFFER_BIT | gl::STENCIL_BUFFER_BIT);
+}
+
+/// Block until every command issued so far has completed on the GPU.
+///
+/// Rarely needed: reading pixels or mapping buffers already waits for the
+/// commands they depend on. Useful for benchmarking a single pass or before
+/// tearing down the context.
+pub fn finish() {
+    unsafe {
+        gl::Finish();
+    }
+}
+
+/// Submit queued commands to the GPU without waiting for them to finish. Do
+/// this after creating a fence that another context or thread will wait on,
+/// otherwise the fence may never be signaled.
+pub fn flush() {
+    unsafe {
+        gl::Flush();
+    }
+}
+
+/// Read a block of pixels from the bound read framebuffer. `x` and `y` are in
+/// GL window coordinates, with the origin at the bottom-left corner, and rows
+/// are returned bottom to top. For a cursor position from the window, use
+/// `window_height - 1 - cursor_y`.
+///
+/// `format` and `r#type` describe the returned data, e.g. `gl::RGBA` and
+/// `gl::UNSIGNED_BYTE`. Reading waits for the rendering it depends on, but the
+/// caller has to bind the right framebuffer and read buffer first. Rows are
+/// tightly packed.
+///
+/// Panics if `r#type` is not a valid pixel type for `format`.
+pub fn read_pixels(
+    x: i32,
+    y: i32,
+    width: i32,
+    height: i32,
+    format: GLenum,
+    r#type: GLenum,
+) -> Vec<u8> {
+    let pixel_size = pixel_size(format, r#type).unwrap_or_else(|| {
+        panic!(
+            "read_pixels: type 0x{:04X} is not supported with format 0x{:04X}",
+            r#type, format
+        )
+    });
+
+    let mut pixels = vec![0u8; width.max(0) as usize * height.max(0) as usize * pixel_size];
+    with_pixel_alignment(gl::PACK_ALIGNMENT, 1, || unsafe {
+        gl::ReadPixels(
+            x,
+            y,
+            width,
+            height,
+            format,
+            r#type,
+            pixels.as_mut_ptr() as *mut c_void,
+        );
+    });
+    check_gl_error("read_pixels");
+
+    pixels
+}
+
+/// Bytes per pixel for client pixel data of `format` and `r#type`, or `None` if
+/// GL doesn't accept the pair.
+fn pixel_size(format: GLenum, r#type: GLenum) -> Option<usize> {
+    let channels = match format {
+        gl::RED
+        | gl::GREEN
+        | gl::BLUE
+        | gl::RED_INTEGER
+        | gl::GREEN_INTEGER
+        | gl::BLUE_INTEGER
+        | gl::DEPTH_COMPONENT
+        | gl::STENCIL_INDEX => 1,
+        gl::RG | gl::RG_INTEGER | gl::DEPTH_STENCIL => 2,
+        gl::RGB | gl::BGR | gl::RGB_INTEGER | gl::BGR_INTEGER => 3,
+        gl::RGBA | gl::BGRA | gl::RGBA_INTEGER | gl::BGRA_INTEGER => 4,
+        _ => return None,
+    };
+    let is_rgb = channels == 3;
+    let is_rgba = channels == 4;
+
+    // Packed types store a whole pixel in one value and only fit some formats.
+    match r#type {
+        gl::UNSIGNED_BYTE_3_3_2 | gl::UNSIGNED_BYTE_2_3_3_REV => is_rgb.then_some(1),
+        gl::UNSIGNED_SHORT_5_6_5 | gl::UNSIGNED_SHORT_5_6_5_REV => is_rgb.then_some(2),
+        gl::UNSIGNED_SHORT_4_4_4_4
+        | gl::UNSIGNED_SHORT_4_4_4_4_REV
+        | gl::UNSIGNED_SHORT_5_5_5_1
+        | gl::UNSIGNED_SHORT_1_5_5_5_REV => is_rgba.then_some(2),
+        gl::UNSIGNED_INT_8_8_8_8
+        | gl::UNSIGNED_INT_8_8_8_8_REV
+        | gl::UNSIGNED_INT_10_10_10_2
+        | gl::UNSIGNED_INT_2_10_10_10_REV => is_rgba.then_some(4),
+        gl::UNSIGNED_INT_10F_11F_11F_REV | gl::UNSIGNED_INT_5_9_9_9_REV => {
+            (format == gl::RGB).then_some(4)
+        }
+        gl::UNSIGNED_INT_24_8 => (format == gl::DEPTH_STENCIL).then_some(4),
+        gl::FLOAT_32_UNSIGNED_INT_24_8_REV => (format == gl::DEPTH_STENCIL).then_some(8),
+        _ if format == gl::DEPTH_STENCIL => None,
+        gl::UNSIGNED_BYTE | gl::BYTE => Some(channels),
+        gl::UNSIGNED_SHORT | gl::SHORT | gl::HALF_FLOAT => Some(channels * 2),
+        gl::UNSIGNED_INT | gl::INT | gl::FLOAT => Some(channels * 4),
+        _ => None,
+    }
+}
+
+/// Read a single 8-bit RGBA pixel, e.g. for color picking. See `read_pixels`
+/// for the coordinate convention.
+pub fn read_pixel_rgba(x: i32, y: i32) -> [u8; 4] {
+    let pixels = read_pixels(x, y, 1, 1, gl::RGBA, gl::UNSIGNED_BYTE);
+    [pixels[0], pixels[1], pixels[2], pixels[3]]
+}
+
+/// Save the default framebuffer as a PNG. Call it after rendering a frame and
+/// before swapping buffers. Leaves the default framebuffer bound for reading.
+#[cfg(feature = "image")]
+pub fn save_screenshot(path: &str, width: u32, height: u32) -> std::io::Result<()> {
+    unsafe {
+        gl::BindFramebuffer(gl::READ_FRAMEBUFFER, 0);
+    }
+    let pixels = read_pixels(
+        0,
+        0,
+        width as i32,
+        height as i32,
+        gl::RGBA,
+        gl::UNSIGNED_BYTE,
+    );
+
+    // GL returns the bottom row first, images start with the top row.
+    let image = image::RgbaImage::from_raw(width, height, pixels)
+        .expect("read_pixels returned the wrong number of bytes");
+    let image = image::imageops::flip_vertical(&image);
+
+    image
+        .save_with_format(path, image::ImageFormat::Png)
+        .map_err(|error| match error {
+            image::ImageError::IoError(error) => error,
+            error => std::io::Error::other(error),
+        })
+}
+
+/// Set the row alignment in bytes (1, 2, 4 or 8) that texture uploads expect,
+/// `GL_UNPACK_ALIGNMENT`. The GL default is 4, which skews 8-bit uploads whose
+/// rows aren't a multiple of 4 bytes. `Texture2D`'s 8-bit loaders handle this
+/// themselves.
+pub fn set_unpack_alignment(alignment: i32) {
+    unsafe {
+        gl::PixelStorei(gl::UNPACK_ALIGNMENT, alignment);
+    }
+}
+
+/// Run `transfer` with `parameter` (`GL_PACK_ALIGNMENT` or
+/// `GL_UNPACK_ALIGNMENT`) set to `alignment`, then restore the previous value.
+fn with_pixel_alignment(parameter: GLenum, alignment: i32, transfer: impl FnOnce()) {
+    let previous = get_integer(parameter);
+    unsafe {
+        gl::PixelStorei(parameter, alignment);
+    }
+    transfer();
+    unsafe {
+        gl::PixelStorei(parameter, previous);
+    }
+}
+
+/// Select the texture unit that later texture binds apply to.
+pub fn set_active_texture(unit: u32) {
+    debug_assert!(
+        unit < max_combined_texture_units(),
+        "texture unit {} exceeds GL_MAX_COMBINED_TEXTURE_IMAGE_UNITS",
+        unit
+    );
+    unsafe {
+        gl::ActiveTexture(gl::TEXTURE0 + unit);
+    }
+}
+
+/// The number of texture units usable across all shader stages combined.
+pub fn max_combined_texture_units() -> u32 {
+    get_integer(gl::MAX_COMBINED_TEXTURE_IMAGE_UNITS).max(0) as u32
+}
+
+thread_local! {
+    static SUPPORTS_DSA: Cell<Option<bool>> = const { Cell::new(None) };
+    static EXTENSIONS: RefCell<Option<HashSet<String>>> = const { RefCell::new(None) };
+}
+
+/// Whether direct state access (GL 4.5 or `GL_ARB_direct_state_access`) is
+/// available, letting wrappers modify objects without binding them. The
+/// answer is cached per thread until `Window::init_gl` loads a new context.
+pub fn supports_dsa() -> bool {
+    SUPPORTS_DSA.with(|cached| {
+        if let Some(supported) = cached.get() {
+            return supported;
+        }
+
+        let supported = context_version() >= (4, 5) || has_extension("GL_ARB_direct_state_access");
+        cached.set(Some(supported));
+        supported
+    })
+}
+
+/// Whether the current context advertises the extension `name`, e.g.
+/// `"GL_ARB_bindless_texture"`. The extension list is read once and cached per
+/// thread until `Window::init_gl` loads a new context.
+pub fn has_extension(name: &str) -> bool {
+    EXTENSIONS.with(|cached| {
+        cached
+            .borrow_mut()
+            .get_or_insert_with(query_extensions)
+            .contains(name)
+    })
+}
+
+fn query_extensions() -> HashSet<String> {
+    (0..get_integer(gl::NUM_EXTENSIONS).max(0) as GLuint)
+        .filter_map(|index| {
+            let extension = unsafe { gl::GetStringi(gl::EXTENSIONS, index) };
+            (!extension.is_null()).then(|| {
+                unsafe { CStr::from_ptr(extension as *const c_char) }
+                    .to_string_lossy()
+                    .into_owned()
+            })
+        })
+        .collect()
+}
+
+/// Limits of the current context, queried once with `GlLimits::query`. The
+/// shader storage and compute limits need GL 4.3 and are 0 on older contexts.
+#[derive(Debug, Clone, Copy)]
+pub struct GlLimits {
+    pub max_texture_size: i32,
+    pub max_array_texture_layers: i32,
+    pub max_cube_map_texture_size: i32,
+    pub max_renderbuffer_size: i32,
+    pub max_samples: i32,
+    pub max_vertex_attribs: i32,
+    pub max_texture_image_units: i32,
+    pub max_combined_texture_image_units: i32,
+    pub max_color_attachments: i32,
+    pub max_draw_buffers: i32,
+    pub max_uniform_block_size: i64,
+    pub max_uniform_buffer_bindings: i32,
+    pub max_shader_storage_block_size: i64,
+    pub max_compute_work_group_invocations: i32,
+}
+
+impl GlLimits {
+    pub fn query() -> GlLimits {
+        // Querying these enums on an older context raises GL_INVALID_ENUM.
+        let has_compute = context_version() >= (4, 3);
+
+        GlLimits {
+            max_texture_size: get_integer(gl::MAX_TEXTURE_SIZE),
+            max_array_texture_layers: get_integer(gl::MAX_ARRAY_TEXTURE_LAYERS),
+            max_cube_map_texture_size: get_integer(gl::MAX_CUBE_MAP_TEXTURE_SIZE),
+            max_renderbuffer_size: get_integer(gl::MAX_RENDERBUFFER_SIZE),
+            max_samples: get_integer(gl::MAX_SAMPLES),
+            max_vertex_attribs: get_integer(gl::MAX_VERTEX_ATTRIBS),
+            max_texture_image_units: get_integer(gl::MAX_TEXTURE_IMAGE_UNITS),
+            max_combined_texture_image_units: get_integer(gl::MAX_COMBINED_TEXTURE_IMAGE_UNITS),
+            max_color_attachments: get_integer(gl::MAX_COLOR_ATTACHMENTS),
+            max_draw_buffers: get_integer(gl::MAX_DRAW_BUFFERS),
+            max_uniform_block_size: get_integer64(gl::MAX_UNIFORM_BLOCK_SIZE),
+            max_uniform_buffer_bindings: get_integer(gl::MAX_UNIFORM_BUFFER_BINDINGS),
+            max_shader_storage_block_size: if has_compute {
+                get_integer64(gl::MAX_SHADER_STORAGE_BLOCK_SIZE)
+            } else {
+                0
+            },
+            max_compute_work_group_invocations: if has_compute {
+                get_integer(gl::MAX_COMPUTE_WORK_GROUP_INVOCATIONS)
+            } else {
+                0
+            },
+        }
+    }
+}
+
+fn get_integer(name: GLenum) -> i32 {
+    let mut value = 0;
+    unsafe {
+        gl::GetIntegerv(name, &mut value);
+    }
+    value
+}
+
+fn get_integer64(name: GLenum) -> i64 {
+    let mut value = 0;
+    unsafe {
+        gl::GetInteger64v(name, &mut value);
+    }
+    value
+}
+
+/// The name of the GPU, e.g. `"NVIDIA GeForce GTX 1060/PCIe/SSE2"`.
+pub fn renderer() -> String {
+    get_string(gl::RENDERER)
+}
+
+/// The company responsible for the GL implementation.
+pub fn vendor() -> String {
+    get_string(gl::VENDOR)
+}
+
+/// The version string of the context, e.g. `"4.5.0 NVIDIA 470.57.02"`.
+pub fn version() -> String {
+    get_string(gl::VERSION)
+}
+
+/// The `(major, minor)` version of the current context.
+pub fn context_version() -> (i32, i32) {
+    (
+        get_integer(gl::MAJOR_VERSION),
+        get_integer(gl::MINOR_VERSION),
+    )
+}
+
+/// Fail with a readable message if the current context is older than
+/// `major.minor`. Call it once after `Window::init_gl`.
+///
+/// ## Example
+/// ```ignore
+/// window.init_gl();
+/// require_version(4, 5).unwrap_or_else(|e| panic!("{}", e));
+/// ```
+pub fn require_version(major: i32, minor: i32) -> Result<(), String> {
+    if context_version() >= (major, minor) {
+        return Ok(());
+    }
+
+    Err(format!(
+        "OpenGL {}.{} is required, but the context only provides {} ({})",
+        major,
+        minor,
+        version(),
+        renderer()
+    ))
+}
+
+fn get_string(name: GLenum) -> String {
+    let value = unsafe { gl::GetString(name) };
+    if value.is_null() {
+        return String::new();
+    }
+
+    unsafe { CStr::from_ptr(value as *const c_char) }
+        .to_string_lossy()
+        .into_owned()
+}
+
+/// Drain the GL error queue and panic with every error found, tagged with
+/// `context` so the failing operation is easy to spot.
+///
+/// Calling `glGetError` forces a sync with the driver, so this is compiled out
+/// of release builds.
+#[cfg(debug_assertions)]
+pub fn check_gl_error(context: &str) {
+    // Without a current context some drivers report errors forever.
+    const MAX_ERRORS: usize = 32;
+
+    let mut errors = Vec::new();
+    while errors.len() < MAX_ERRORS {
+        let error = unsafe { gl::GetError() };
+        if error == gl::NO_ERROR {
+            break;
+        }
+        errors.push(gl_error_name(error));
+    }
+
+    if !errors.is_empty() {
+        panic!("OpenGL error in {}: {}", context, errors.join(", "));
+    }
+}
+
+#[cfg(not(debug_assertions))]
+#[inline(always)]
+pub fn check_gl_error(_context: &str) {}
+
+#[cfg(debug_assertions)]
+fn gl_error_name(error: GLenum) -> String {
+    match error {
+        gl::INVALID_ENUM => "GL_INVALID_ENUM".to_string(),
+        gl::INVALID_VALUE => "GL_INVALID_VALUE".to_string(),
+        gl::INVALID_OPERATION => "GL_INVALID_OPERATION".to_string(),
+        gl::INVALID_FRAMEBUFFER_OPERATION => "GL_INVALID_FRAMEBUFFER_OPERATION".to_string(),
+        gl::OUT_OF_MEMORY => "GL_OUT_OF_MEMORY".to_string(),
+        gl::STACK_UNDERFLOW => "GL_STACK_UNDERFLOW".to_string(),
+        gl::STACK_OVERFLOW => "GL_STACK_OVERFLOW".to_string(),
+        _ => format!("0x{:04X}", error),
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum DebugSeverity {
+    Notification,
+    Low,
+    Medium,
+    High,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DebugSource {
+    Api,
+    WindowSystem,
+    ShaderCompiler,
+    ThirdParty,
+    Application,
+    Other,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DebugType {
+    Error,
+    DeprecatedBehavior,
+    UndefinedBehavior,
+    Portability,
+    Performance,
+    Marker,
+    PushGroup,
+    PopGroup,
+    Other,
+}
+
+/// A message reported by the driver through `KHR_debug`.
+#[derive(Debug, Clone)]
+pub struct DebugMessage {
+    pub source: DebugSource,
+    pub r#type: DebugType,
+    pub severity: DebugSeverity,
+    pub id: u32,
+    pub message: String,
+}
+
+struct DebugOutput {
+    min_severity: DebugSeverity,
+    callback: Box<dyn Fn(&DebugMessage)>,
+}
+
+/// # Debug Output
+/// Install `callback` to receive driver messages of at least `min_severity`.
+///
+/// Messages are delivered synchronously, so a backtrace taken in the callback
+/// points at the offending GL call. The richest output requires a debug
+/// context, which `Window` requests in debug builds. Calling this again
+/// replaces the previous callback. A panic in the callback can't unwind into
+/// the driver, so it is caught and the message dropped.
+///
+/// Returns `false` if the context doesn't support `KHR_debug`.
+///
+/// ## Example
+/// ```ignore
+/// enable_debug_output(DebugSeverity::Low, |message| {
+///     logger::warn!("{:?} {:?}: {}", message.source, message.r#type, message.message);
+/// });
+/// ```
+pub fn enable_debug_output<F>(min_severity: DebugSeverity, callback: F) -> bool
+where
+    F: Fn(&DebugMessage) + 'static,
+{
+    if !supports_khr_debug() {
+        return false;
+    }
+
+    let output = Box::new(DebugOutput {
+        min_severity,
+        callback: Box::new(callback),
+    });
+
+    unsafe {
+        gl::Enable(gl::DEBUG_OUTPUT);
+        gl::Enable(gl::DEBUG_OUTPUT_SYNCHRONOUS);
+        gl::DebugMessageCallback(
+            Some(debug_message_callback),
+            &*output as *const DebugOutput as *const c_void,
+        );
+    }
+
+    // The driver may call back until the context is destroyed, so the output is
+    // kept alive here. The previous one is freed now that GL no longer uses it.
+    DEBUG_OUTPUT.with(|installed| installed.borrow_mut().replace(output));
+
+    true
+}
+
+thread_local! {
+    static DEBUG_OUTPUT: RefCell<Option<Box<DebugOutput>>> = const { RefCell::new(None) };
+}
+
+extern "system" fn debug_message_callback(
+    source: GLenum,
+    r#type: GLenum,
+    id: GLuint,
+    severity: GLenum,
+    length: GLsizei,
+    message: *const GLchar,
+    user_param: *mut c_void,
+) {
+    // Unwinding out of an `extern "system"` fn into the driver aborts the process.
+    let _ = panic::catch_unwind(AssertUnwindSafe(|| {
+        let output = unsafe { &*(user_param as *const DebugOutput) };
+        handle_debug_message(output, source, r#type, id, severity, length, message);
+    }));
+}
+
+fn handle_debug_message(
+    output: &DebugOutput,
+    source: GLenum,
+    r#type: GLenum,
+    id: GLuint,
+    severity: GLenum,
+    length: GLsizei,
+    message: *const GLchar,
+) {
+    let severity = match severity {
+        gl::DEBUG_SEVERITY_HIGH => DebugSeverity::High,
+        gl::DEBUG_SEVERITY_MEDIUM => DebugSeverity::Medium,
+        gl::DEBUG_SEVERITY_LOW => DebugSeverity::Low,
+        _ => DebugSeverity::Notification,
+    };
+    if severity < output.min_severity {
+        return;
+    }
+
+    let source = match source {
+        gl::DEBUG_SOURCE_API => DebugSource::Api,
+        gl::DEBUG_SOURCE_WINDOW_SYSTEM => DebugSource::WindowSystem,
+        gl::DEBUG_SOURCE_SHADER_COMPILER => DebugSource::ShaderCompiler,
+        gl::DEBUG_SOURCE_THIRD_PARTY => DebugSource::ThirdParty,
+        gl::DEBUG_SOURCE_APPLICATION => DebugSource::Application,
+        _ => DebugSource::Other,
+    };
+
+    let r#type = match r#type {
+        gl::DEBUG_TYPE_ERROR => DebugType::Error,
+        gl::DEBUG_TYPE_DEPRECATED_BEHAVIOR => DebugType::DeprecatedBehavior,
+        gl::DEBUG_TYPE_UNDEFINED_BEHAVIOR => DebugType::UndefinedBehavior,
+        gl::DEBUG_TYPE_PORTABILITY => DebugType::Portability,
+        gl::DEBUG_TYPE_PERFORMANCE => DebugType::Performance,
+        gl::DEBUG_TYPE_MARKER => DebugType::Marker,
+        gl::DEBUG_TYPE_PUSH_GROUP => DebugType::PushGroup,
+        gl::DEBUG_TYPE_POP_GROUP => DebugType::PopGroup,
+        _ => DebugType::Other,
+    };
+
+    let message = if length < 0 {
+        unsafe { CStr::from_ptr(message) }
+            .to_string_lossy()
+            .into_owned()
+    } else {
+        let bytes = unsafe { slice::from_raw_parts(message as *const u8, length as usize) };
+        String::from_utf8_lossy(bytes).into_owned()
+    };
+
+    (output.callback)(&DebugMessage {
+        source,
+        r#type,
+        severity,
+        id,
+        message,
+    });
+}
@@ -0,0 +1,209 @@
+use std::collections::HashMap;
+use std::{mem, slice};
+
+#[cfg(feature = "tobj")]
+use crate::custom_errors::MeshError;
+use crate::graphics::gl_wrapper::{
+    draw_arrays, BufferObject, BufferUsage, ElementBuffer, PlainData, Vao, VertexLayout,
+};
+
+/// # Mesh
+/// Owns a VAO, its vertex buffer, an optional index buffer and the vertex layout.
+/// All of them are deleted when the mesh is dropped.
+///
+/// ## Example
+/// ```ignore
+/// let layout = VertexLayout::new().push_f32(3).push_f32(2);
+/// let mesh = Mesh::new(&vertices, Some(&indices), layout);
+///
+/// program.bind();
+/// mesh.draw();
+/// ```
+pub struct Mesh {
+    vao: Vao,
+    vbo: BufferObject,
+    ebo: Option<ElementBuffer>,
+    layout: VertexLayout,
+    vertex_count: i32,
+}
+
+impl Mesh {
+    /// Create a mesh from vertices of any `#[repr(C)]` type matching `layout`.
+    pub fn new<T: Copy>(vertices: &[T], indices: Option<&[u32]>, layout: VertexLayout) -> Mesh {
+        let vao = Vao::new();
+        vao.bind();
+
+        let vbo = BufferObject::array_buffer();
+        vbo.bind();
+        vbo.store_data(vertices);
+
+        let ebo = indices.map(|indices| {
+            let mut ebo = ElementBuffer::new(BufferUsage::StaticDraw);
+            ebo.bind();
+            ebo.store_u32(indices);
+            ebo
+        });
+
+        layout.apply();
+        vao.unbind();
+
+        let vertex_count = mem::size_of_val(vertices)
+            .checked_div(layout.stride())
+            .unwrap_or(0) as i32;
+
+        Mesh {
+            vao,
+            vbo,
+            ebo,
+            layout,
+            vertex_count,
+        }
+    }
+
+    /// Load every model in an OBJ file into one indexed mesh.
+    ///
+    /// Vertices are laid out as position (3 floats), normal (3 floats) and UV
+    /// (2 floats). Missing UVs are zero, and if the file has no normals, flat
+    /// normals are computed per face.
+    #[cfg(feature = "tobj")]
+    pub fn load_obj(path: &str) -> Result<Mesh, MeshError> {
+        let options = tobj::LoadOptions {
+            single_index: true,
+            triangulate: true,
+            ..Default::default()
+        };
+        let (models, _materials) =
+            tobj::load_obj(path, &options).map_err(|source| MeshError::Obj {
+                path: path.to_string(),
+                source,
+            })?;
+
+        let mut vertices: Vec<[f32; 8]> = Vec::new();
+        let mut indices = Vec::new();
+        for model in &models {
+            let mesh = &model.mesh;
+            let first_vertex = vertices.len() as u32;
+            let has_normals = !mesh.normals.is_empty();
+            let has_uvs = !mesh.texcoords.is_empty();
+
+            for vertex in 0..mesh.positions.len() / 3 {
+                let position = &mesh.positions[vertex * 3..vertex * 3 + 3];
+                let normal = if has_normals {
+                    &mesh.normals[vertex * 3..vertex * 3 + 3]
+                } else {
+                    &[0.0; 3]
+                };
+                let uv = if has_uvs {
+                    &mesh.texcoords[vertex * 2..vertex * 2 + 2]
+                } else {
+                    &[0.0; 2]
+                };
+                vertices.push([
+                    position[0],
+                    position[1],
+                    position[2],
+                    normal[0],
+                    normal[1],
+                    normal[2],
+                    uv[0],
+                    uv[1],
+                ]);
+            }
+
+            if has_normals {
+                indices.extend(mesh.indices.iter().map(|index| first_vertex + index));
+            } else {
+                // Flat normals need one vertex per face corner, so unweld the
+                // faces and let `build_indexed` merge what can still be shared.
+                let mut flat = Vec::with_capacity(mesh.indices.len());
+                for face in mesh.indices.chunks_exact(3) {
+                    let mut corners = [face[0], face[1], face[2]]
+                        .map(|index| vertices[(first_vertex + index) as usize]);
+                    let normal = face_normal(&corners);
+                    for corner in &mut corners {
+                        corner[3..6].copy_from_slice(&normal);
+                    }
+                    flat.extend_from_slice(&corners);
+                }
+
+                vertices.truncate(first_vertex as usize);
+                let (unique, unique_indices) = build_indexed(&flat);
+                vertices.extend_from_slice(&unique);
+                indices.extend(unique_indices.iter().map(|index| first_vertex + index));
+            }
+        }
+
+        let layout = VertexLayout::new().push_f32(3).push_f32(3).push_f32(2);
+        Ok(Mesh::new(&vertices, Some(&indices), layout))
+    }
+
+    /// Draw the mesh as triangles with the currently bound shader program.
+    pub fn draw(&self) {
+        self.vao.bind();
+        if let Some(ebo) = &self.ebo {
+            ebo.draw(gl::TRIANGLES);
+        } else {
+            draw_arrays(gl::TRIANGLES, 0, self.vertex_count);
+        }
+        self.vao.unbind();
+    }
+
+    pub fn vbo(&self) -> &BufferObject {
+        &self.vbo
+    }
+
+    pub fn layout(&self) -> &VertexLayout {
+        &self.layout
+    }
+
+    pub fn vertex_count(&self) -> i32 {
+        self.vertex_count
+    }
+
+    pub fn index_count(&self) -> i32 {
+        self.ebo.as_ref().map_or(0, ElementBuffer::count)
+    }
+}
+
+#[cfg(feature = "tobj")]
+fn face_normal(corners: &[[f32; 8]; 3]) -> [f32; 3] {
+    use cgmath::{InnerSpace, Vector3};
+
+    let position = |corner: &[f32; 8]| Vector3::new(corner[0], corner[1], corner[2]);
+    let normal = (position(&corners[1]) - position(&corners[0]))
+        .cross(position(&corners[2]) - position(&corners[0]));
+    if normal.magnitude2() == 0.0 {
+        return [0.0; 3];
+    }
+
+    normal.normalize().into()
+}
+
+/// Deduplicate a flat vertex stream into unique vertices plus an index buffer
+/// that can be passed straight to `Mesh::new`.
+///
+/// Vertices are compared bitwise, so they have to be `PlainData` such as
+/// `[f32; 8]`, which has no padding bytes.
+///
+/// ## Example
+/// ```ignore
+/// let (vertices, indices) = build_indexed(&obj_vertices);
+/// let mesh = Mesh::new(&vertices, Some(&indices), layout);
+/// ```
+pub fn build_indexed<T: PlainData>(vertices: &[T]) -> (Vec<T>, Vec<u32>) {
+    let mut unique = Vec::new();
+    let mut indices = Vec::with_capacity(vertices.len());
+    let mut seen: HashMap<&[u8], u32> = HashMap::with_capacity(vertices.len());
+
+    for vertex in vertices {
+        let bytes =
+            unsafe { slice::from_raw_parts(vertex as *const T as *const u8, mem::size_of::<T>()) };
+        let index = *seen.entry(bytes).or_insert_with(|| {
+            unique.push(*vertex);
+            (unique.len() - 1) as u32
+        });
+        indices.push(index);
+    }
+
+    (unique, indices)
+}
@@ -1,2 +1,12 @@
+pub mod camera;
+pub mod color;
 pub mod gl_wrapper;
+pub mod mesh;
+pub mod screen_quad;
+pub mod sprite_batch;
+#[cfg(test)]
+mod tests;
+#[cfg(feature = "fontdue")]
+pub mod text_renderer;
+pub mod transform;
 pub mod window;
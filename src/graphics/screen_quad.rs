@@ -0,0 +1,70 @@
+use crate::graphics::gl_wrapper::{draw_arrays, BufferObject, Vao, VertexLayout};
+
+/// Position and UV of a single triangle covering the whole screen. The parts
+/// outside clip space are clipped away, which avoids the diagonal seam of a
+/// two-triangle quad.
+#[rustfmt::skip]
+const VERTICES: [f32; 12] = [
+    -1.0, -1.0, 0.0, 0.0,
+     3.0, -1.0, 2.0, 0.0,
+    -1.0,  3.0, 0.0, 2.0,
+];
+
+/// # Screen Quad
+/// Full-screen geometry for post-processing passes. Bind the post-processing
+/// program and its input texture, then call `draw`. The vertex shader receives
+/// these inputs:
+/// ```glsl
+/// layout (location = 0) in vec2 position;
+/// layout (location = 1) in vec2 uv;
+///
+/// out vec2 frag_uv;
+///
+/// void main() {
+///     frag_uv = uv;
+///     gl_Position = vec4(position, 0.0, 1.0);
+/// }
+/// ```
+///
+/// ## Example
+/// ```ignore
+/// let screen_quad = ScreenQuad::new();
+///
+/// post_process.bind();
+/// scene_color.bind_unit(0);
+/// post_process.set_uniform_i32("scene", 0);
+/// screen_quad.draw();
+/// ```
+pub struct ScreenQuad {
+    vao: Vao,
+    _vbo: BufferObject,
+}
+
+impl ScreenQuad {
+    pub fn new() -> ScreenQuad {
+        let vao = Vao::new();
+        vao.bind();
+
+        let vbo = BufferObject::array_buffer();
+        vbo.bind();
+        vbo.store_f32_data(&VERTICES);
+
+        VertexLayout::new().push_f32(2).push_f32(2).apply();
+        vao.unbind();
+
+        ScreenQuad { vao, _vbo: vbo }
+    }
+
+    /// Draw the screen triangle with the currently bound shader program.
+    pub fn draw(&self) {
+        self.vao.bind();
+        draw_arrays(gl::TRIANGLES, 0, 3);
+        self.vao.unbind();
+    }
+}
+
+impl Default for ScreenQuad {
+    fn default() -> ScreenQuad {
+        ScreenQuad::new()
+    }
+}
@@ -0,0 +1,172 @@
+use cgmath::Vector2;
+use gl::types::GLuint;
+
+use crate::graphics::color::Color;
+use crate::graphics::gl_wrapper::{
+    draw_elements, BufferObject, BufferTarget, BufferUsage, Texture2D, Vao, VertexLayout,
+};
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct SpriteVertex {
+    position: [f32; 2],
+    uv: [f32; 2],
+    color: [f32; 4],
+}
+
+/// # Sprite Batch
+/// Collects textured quads and draws all quads sharing a texture with a single
+/// draw call. Switching textures flushes the quads queued so far. Each flush
+/// binds its texture again, so other binds in between don't matter, but a
+/// texture must not be dropped while quads using it are still queued.
+///
+/// The batch only supplies geometry; bind a shader program with these inputs
+/// before drawing:
+/// ```glsl
+/// layout (location = 0) in vec2 position;
+/// layout (location = 1) in vec2 uv;
+/// layout (location = 2) in vec4 color;
+/// ```
+///
+/// ## Example
+/// ```ignore
+/// let mut batch = SpriteBatch::new();
+///
+/// program.bind();
+/// batch.begin();
+/// batch.draw(&player, vec2(10.0, 20.0), vec2(32.0, 32.0), [0.0, 0.0, 1.0, 1.0], Color::WHITE);
+/// batch.draw(&enemy, vec2(50.0, 20.0), vec2(32.0, 32.0), [0.0, 0.0, 1.0, 1.0], Color::WHITE);
+/// batch.end();
+/// ```
+pub struct SpriteBatch {
+    vao: Vao,
+    vbo: BufferObject,
+    ebo: BufferObject,
+    vertices: Vec<SpriteVertex>,
+    index_capacity: usize,
+    /// GL name of the texture the queued quads use.
+    texture: Option<GLuint>,
+}
+
+impl SpriteBatch {
+    pub fn new() -> SpriteBatch {
+        let vao = Vao::new();
+        vao.bind();
+
+        let vbo = BufferObject::new(BufferTarget::Array, BufferUsage::DynamicDraw);
+        vbo.bind();
+        let ebo = BufferObject::new(BufferTarget::ElementArray, BufferUsage::DynamicDraw);
+        ebo.bind();
+
+        VertexLayout::new()
+            .push_f32(2)
+            .push_f32(2)
+            .push_f32(4)
+            .apply();
+        vao.unbind();
+
+        SpriteBatch {
+            vao,
+            vbo,
+            ebo,
+            vertices: Vec::new(),
+            index_capacity: 0,
+            texture: None,
+        }
+    }
+
+    /// Start a new batch, discarding anything queued since the last `end`.
+    pub fn begin(&mut self) {
+        self.vertices.clear();
+        self.texture = None;
+    }
+
+    /// Queue a quad with its top-left corner at `position`. `uv` is
+    /// `[u0, v0, u1, v1]`.
+    pub fn draw(
+        &mut self,
+        texture: &Texture2D,
+        position: Vector2<f32>,
+        size: Vector2<f32>,
+        uv: [f32; 4],
+        color: Color,
+    ) {
+        if self.texture != Some(texture.id()) {
+            self.flush();
+            self.texture = Some(texture.id());
+        }
+
+        let [u0, v0, u1, v1] = uv;
+        let (x0, y0) = (position.x, position.y);
+        let (x1, y1) = (position.x + size.x, position.y + size.y);
+        let color = color.to_array();
+        self.vertices.extend_from_slice(&[
+            SpriteVertex {
+                position: [x0, y0],
+                uv: [u0, v0],
+                color,
+            },
+            SpriteVertex {
+                position: [x1, y0],
+                uv: [u1, v0],
+                color,
+            },
+            SpriteVertex {
+                position: [x1, y1],
+                uv: [u1, v1],
+                color,
+            },
+            SpriteVertex {
+                position: [x0, y1],
+                uv: [u0, v1],
+                color,
+            },
+        ]);
+    }
+
+    /// Draw everything queued since the last flush.
+    pub fn end(&mut self) {
+        self.flush();
+        self.texture = None;
+    }
+
+    fn flush(&mut self) {
+        let Some(texture) = self.texture else {
+            return;
+        };
+        if self.vertices.is_empty() {
+            return;
+        }
+
+        unsafe {
+            gl::BindTexture(gl::TEXTURE_2D, texture);
+        }
+
+        let quad_count = self.vertices.len() / 4;
+        self.vao.bind();
+        self.vbo.bind();
+        self.vbo.store_data(&self.vertices);
+
+        if quad_count > self.index_capacity {
+            let indices: Vec<u32> = (0..quad_count as u32)
+                .flat_map(|quad| {
+                    let first = quad * 4;
+                    [first, first + 1, first + 2, first + 2, first + 3, first]
+                })
+                .collect();
+            self.ebo.bind();
+            self.ebo.store_u32_data(&indices);
+            self.index_capacity = quad_count;
+        }
+
+        draw_elements(gl::TRIANGLES, (quad_count * 6) as i32, gl::UNSIGNED_INT);
+        self.vao.unbind();
+        self.vertices.clear();
+    }
+}
+
+impl Default for SpriteBatch {
+    fn default() -> SpriteBatch {
+        SpriteBatch::new()
+    }
+}
@@ -0,0 +1,364 @@
+//! Tests for the graphics module.
+//!
+//! Tests marked `#[ignore]` need a GL context, which `Window::new_hidden`
+//! creates. Run them on a machine with a display, or under a virtual one on CI:
+//! `xvfb-run cargo test -- --ignored`. The other GL tests load mock functions
+//! that record what the wrappers call instead.
+
+use std::mem;
+use std::os::raw::c_void;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Mutex, MutexGuard};
+
+use gl::types::*;
+
+use crate::graphics::color::Color;
+use crate::graphics::gl_wrapper::*;
+use crate::graphics::mesh::build_indexed;
+use crate::graphics::window::Window;
+
+/// GL function pointers are global and GLFW must not be initialized from
+/// several threads at once, so tests that touch either run one at a time.
+static GL_LOCK: Mutex<()> = Mutex::new(());
+
+fn lock_gl() -> MutexGuard<'static, ()> {
+    GL_LOCK
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// Run `test` with a hidden window's GL context current on this thread.
+fn with_context(test: impl FnOnce()) {
+    let _lock = lock_gl();
+    let _window = Window::new_hidden(64, 64);
+    test();
+}
+
+/// Link `vertex_shader` on its own, capturing its `captured` output with
+/// transform feedback.
+fn capture_program(vertex_shader: &str) -> ShaderProgram {
+    ShaderProgram::builder()
+        .vertex(vertex_shader)
+        .transform_feedback_varyings(&["captured"], gl::INTERLEAVED_ATTRIBS)
+        .build()
+        .unwrap_or_else(|e| panic!("{}", e))
+}
+
+/// Draw `vertex_count` points from the bound VAO with rasterization disabled
+/// and return the value of `captured` for each vertex.
+fn capture_points<T: PlainData>(program: &ShaderProgram, vertex_count: usize) -> Vec<T> {
+    let captured = BufferObject::new(BufferTarget::TransformFeedback, BufferUsage::StaticRead);
+    captured.bind();
+    captured.allocate(vertex_count * mem::size_of::<T>());
+
+    let feedback = TransformFeedback::new();
+    feedback.bind();
+    feedback.bind_buffer(0, &captured);
+
+    program.bind();
+    unsafe {
+        gl::Enable(gl::RASTERIZER_DISCARD);
+    }
+    feedback.begin(gl::POINTS);
+    draw_arrays(gl::POINTS, 0, vertex_count as i32);
+    feedback.end();
+    unsafe {
+        gl::Disable(gl::RASTERIZER_DISCARD);
+    }
+    feedback.unbind();
+
+    captured.read_to_vec(vertex_count)
+}
+
+/// Buffer names deleted through the mock `glDeleteBuffers`.
+static DELETED_BUFFERS: AtomicUsize = AtomicUsize::new(0);
+
+/// Size passed to the last mock `glBufferData` call.
+static BUFFER_DATA_SIZE: AtomicUsize = AtomicUsize::new(usize::MAX);
+
+/// Replace the GL functions the buffer wrappers use with mocks. The context
+/// then reports version 0.0 without extensions, so nothing uses DSA. Hold
+/// `lock_gl` while the mocks are loaded; `with_context` loads the real
+/// functions again.
+fn load_mock_gl() {
+    gl::GetIntegerv::load_with(|_| mock_get_integerv as *const c_void);
+    gl::GenBuffers::load_with(|_| mock_gen_buffers as *const c_void);
+    gl::DeleteBuffers::load_with(|_| mock_delete_buffers as *const c_void);
+    gl::BindBuffer::load_with(|_| mock_bind_buffer as *const c_void);
+    gl::BufferData::load_with(|_| mock_buffer_data as *const c_void);
+    gl::GetError::load_with(|_| mock_get_error as *const c_void);
+    forget_context();
+    DELETED_BUFFERS.store(0, Ordering::SeqCst);
+    BUFFER_DATA_SIZE.store(usize::MAX, Ordering::SeqCst);
+}
+
+extern "system" fn mock_get_integerv(_name: GLenum, data: *mut GLint) {
+    unsafe { *data = 0 }
+}
+
+extern "system" fn mock_gen_buffers(count: GLsizei, buffers: *mut GLuint) {
+    for index in 0..count as usize {
+        unsafe { *buffers.add(index) = index as GLuint + 1 }
+    }
+}
+
+extern "system" fn mock_delete_buffers(count: GLsizei, _buffers: *const GLuint) {
+    DELETED_BUFFERS.fetch_add(count as usize, Ordering::SeqCst);
+}
+
+extern "system" fn mock_bind_buffer(_target: GLenum, _buffer: GLuint) {}
+
+extern "system" fn mock_buffer_data(
+    _target: GLenum,
+    size: GLsizeiptr,
+    _data: *const c_void,
+    _usage: GLenum,
+) {
+    BUFFER_DATA_SIZE.store(size as usize, Ordering::SeqCst);
+}
+
+extern "system" fn mock_get_error() -> GLenum {
+    gl::NO_ERROR
+}
+
+#[test]
+fn vertex_layout_computes_stride_and_offsets() {
+    let layout = VertexLayout::new().push_f32(3).push_f32(2).push_u32(4);
+
+    assert_eq!(layout.stride(), 36);
+    assert_eq!(layout.offset(0), Some(0));
+    assert_eq!(layout.offset(1), Some(12));
+    assert_eq!(layout.offset(2), Some(20));
+    assert_eq!(layout.offset(3), None);
+}
+
+#[test]
+fn empty_vertex_layout_has_no_stride() {
+    let layout = VertexLayout::new();
+
+    assert_eq!(layout.stride(), 0);
+    assert_eq!(layout.offset(0), None);
+}
+
+#[test]
+fn build_indexed_merges_duplicate_vertices() {
+    let vertices = [
+        [0.0f32, 0.0],
+        [1.0, 0.0],
+        [0.0, 0.0],
+        [1.0, 0.0],
+        [0.0, 1.0],
+    ];
+
+    let (unique, indices) = build_indexed(&vertices);
+
+    assert_eq!(unique, vec![[0.0, 0.0], [1.0, 0.0], [0.0, 1.0]]);
+    assert_eq!(indices, vec![0, 1, 0, 1, 2]);
+}
+
+#[test]
+fn build_indexed_handles_empty_input() {
+    let (unique, indices) = build_indexed::<[f32; 3]>(&[]);
+
+    assert!(unique.is_empty());
+    assert!(indices.is_empty());
+}
+
+#[test]
+fn color_from_hex_parses_rgb_and_rgba() {
+    assert_eq!(
+        Color::from_hex("#ff8800"),
+        Some(Color::rgb(1.0, 136.0 / 255.0, 0.0))
+    );
+    assert_eq!(
+        Color::from_hex("00ff0080"),
+        Some(Color::rgba(0.0, 1.0, 0.0, 128.0 / 255.0))
+    );
+}
+
+#[test]
+fn color_from_hex_rejects_invalid_strings() {
+    assert_eq!(Color::from_hex("#ff88"), None);
+    assert_eq!(Color::from_hex("#gg0000"), None);
+    assert_eq!(Color::from_hex("#ffé000"), None);
+    assert_eq!(Color::from_hex("#+f+f+f"), None);
+    assert_eq!(Color::from_hex(""), None);
+}
+
+#[test]
+#[ignore = "needs a display for the GL context"]
+fn hidden_window_provides_a_context() {
+    with_context(|| {
+        assert!(context_version() >= (3, 0));
+        assert!(!version().is_empty());
+    });
+}
+
+#[test]
+fn dropping_a_buffer_deletes_it_once() {
+    let _lock = lock_gl();
+    load_mock_gl();
+
+    {
+        let _buffer = BufferObject::array_buffer();
+        assert_eq!(DELETED_BUFFERS.load(Ordering::SeqCst), 0);
+    }
+    assert_eq!(DELETED_BUFFERS.load(Ordering::SeqCst), 1);
+
+    // A released buffer belongs to the caller and is not deleted again.
+    let id = BufferObject::array_buffer().into_raw();
+    assert_eq!(id, 1);
+    assert_eq!(DELETED_BUFFERS.load(Ordering::SeqCst), 1);
+}
+
+#[test]
+#[ignore = "needs a display for the GL context"]
+fn integer_attributes_reach_the_shader_unconverted() {
+    with_context(|| {
+        let program = capture_program(
+            "#version 330 core
+            layout (location = 0) in uvec4 bone_ids;
+            flat out uvec4 captured;
+
+            void main() {
+                captured = bone_ids;
+                gl_Position = vec4(0.0);
+            }",
+        );
+
+        // u32::MAX and 16777217 can't survive a round trip through f32.
+        let bone_ids = [[0u32, 1, 16_777_217, u32::MAX], [7, 6, 5, 4]];
+        let vao = Vao::new();
+        vao.bind();
+        let vbo = BufferObject::array_buffer();
+        vbo.bind();
+        vbo.store_data(&bone_ids);
+        VertexLayout::new().push_u32(4).apply();
+
+        let captured: Vec<[u32; 4]> = capture_points(&program, bone_ids.len());
+        assert_eq!(captured, bone_ids);
+    });
+}
+
+#[test]
+#[ignore = "needs a display for the GL context"]
+fn uniform_buffer_contents_reach_the_shader() {
+    with_context(|| {
+        let program = capture_program(
+            "#version 330 core
+            layout (std140) uniform Values {
+                vec4 first;
+                vec4 second;
+            };
+            out vec4 captured;
+
+            void main() {
+                captured = gl_VertexID == 0 ? first : second;
+                gl_Position = vec4(0.0);
+            }",
+        );
+
+        let values = [1.0f32, 2.0, 3.0, 4.0, -0.5, 0.25, 100.0, 0.0];
+        let ubo = UniformBuffer::new(BufferUsage::StaticDraw);
+        ubo.bind();
+        ubo.store_f32_data(&values);
+        ubo.bind_to_point(0);
+        assert!(program.bind_uniform_block("Values", 0));
+
+        let vao = Vao::new();
+        vao.bind();
+        let captured: Vec<[f32; 4]> = capture_points(&program, 2);
+        assert_eq!(captured, [[1.0, 2.0, 3.0, 4.0], [-0.5, 0.25, 100.0, 0.0]]);
+    });
+}
+
+#[test]
+fn storing_an_empty_slice_allocates_nothing() {
+    let _lock = lock_gl();
+    load_mock_gl();
+
+    let buffer = BufferObject::array_buffer();
+    buffer.bind();
+    buffer.store_f32_data(&[]);
+
+    assert_eq!(BUFFER_DATA_SIZE.load(Ordering::SeqCst), 0);
+    assert_eq!(buffer.byte_len(), 0);
+    assert_eq!(buffer.len(), 0);
+    assert!(buffer.is_empty());
+}
+
+#[test]
+#[ignore = "needs a display for the GL context"]
+fn fragment_outputs_reach_every_draw_buffer() {
+    with_context(|| {
+        let program = ShaderProgram::from_source(
+            "#version 330 core
+            void main() {
+                // One triangle that covers the whole viewport.
+                vec2 corner = vec2(gl_VertexID & 1, gl_VertexID >> 1);
+                gl_Position = vec4(corner * 4.0 - 1.0, 0.0, 1.0);
+            }",
+            "#version 330 core
+            layout (location = 0) out vec4 first;
+            layout (location = 1) out vec4 second;
+            layout (location = 2) out vec4 third;
+
+            void main() {
+                first = vec4(1.0, 0.0, 0.0, 1.0);
+                second = vec4(0.0, 1.0, 0.0, 1.0);
+                third = vec4(0.0, 0.0, 1.0, 1.0);
+            }",
+        )
+        .unwrap_or_else(|e| panic!("{}", e));
+
+        let targets: Vec<Texture2D> = (0..3)
+            .map(|_| Texture2D::with_storage(4, 4, 1, gl::RGBA8))
+            .collect();
+        let framebuffer = Framebuffer::new();
+        framebuffer.bind();
+        for (index, target) in targets.iter().enumerate() {
+            framebuffer.attach_color_texture(target, index as u32);
+        }
+        framebuffer.set_draw_buffers(&[0, 1, 2]);
+        assert_eq!(framebuffer.check_complete(), Ok(()));
+
+        set_viewport(0, 0, 4, 4);
+        program.bind();
+        let vao = Vao::new();
+        vao.bind();
+        draw_arrays(gl::TRIANGLES, 0, 3);
+
+        let expected = [[255, 0, 0, 255], [0, 255, 0, 255], [0, 0, 255, 255]];
+        for (index, color) in expected.iter().enumerate() {
+            unsafe {
+                gl::ReadBuffer(gl::COLOR_ATTACHMENT0 + index as GLenum);
+            }
+            assert_eq!(&read_pixel_rgba(2, 2), color, "attachment {}", index);
+        }
+        framebuffer.unbind();
+    });
+}
+
+#[test]
+#[ignore = "needs a display for the GL context"]
+fn shader_cache_does_not_outlive_its_context() {
+    const VERTEX_SHADER: &str = "#version 330 core
+        void main() {
+            gl_Position = vec4(0.0);
+        }";
+    const FRAGMENT_SHADER: &str = "#version 330 core
+        out vec4 color;
+
+        void main() {
+            color = vec4(1.0);
+        }";
+
+    // The second context must compile its own shaders instead of reusing the
+    // names cached for the first, which was destroyed with its window.
+    for _ in 0..2 {
+        with_context(|| {
+            ShaderProgram::from_source(VERTEX_SHADER, FRAGMENT_SHADER)
+                .unwrap_or_else(|e| panic!("{}", e));
+        });
+    }
+}
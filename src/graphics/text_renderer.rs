@@ -0,0 +1,224 @@
+use std::collections::HashMap;
+
+use crate::custom_errors::FontError;
+use crate::graphics::camera::Camera;
+use crate::graphics::color::Color;
+use crate::graphics::gl_wrapper::{
+    draw_arrays, BufferObject, BufferTarget, BufferUsage, ShaderProgram, Texture2D, Vao,
+    VertexLayout,
+};
+
+const VERTEX_SHADER: &str = r#"#version 330 core
+layout (location = 0) in vec2 position;
+layout (location = 1) in vec2 uv;
+
+uniform mat4 projection;
+
+out vec2 frag_uv;
+
+void main() {
+    frag_uv = uv;
+    gl_Position = projection * vec4(position, 0.0, 1.0);
+}
+"#;
+
+const FRAGMENT_SHADER: &str = r#"#version 330 core
+in vec2 frag_uv;
+
+uniform sampler2D atlas;
+uniform vec4 color;
+
+out vec4 out_color;
+
+void main() {
+    out_color = vec4(color.rgb, color.a * texture(atlas, frag_uv).r);
+}
+"#;
+
+/// Width of the glyph atlas in pixels. Rows are added as needed.
+const ATLAS_WIDTH: usize = 512;
+/// Empty pixels around each glyph so linear filtering doesn't bleed.
+const GLYPH_PADDING: usize = 1;
+
+struct Glyph {
+    metrics: fontdue::Metrics,
+    /// `[u0, v0, u1, v1]` in the atlas, from the glyph's top-left corner.
+    uv: [f32; 4],
+}
+
+/// # Text Renderer
+/// Rasterizes the printable ASCII characters of a font into a glyph atlas
+/// once, then draws strings as textured quads, one draw call per string.
+///
+/// Coordinates are in pixels with the origin at the top-left corner of the
+/// viewport. Blending has to be enabled for the glyph edges to look smooth.
+///
+/// ## Example
+/// ```ignore
+/// let mut text = TextRenderer::new(include_bytes!("fonts/roboto.ttf"), 32.0)?;
+/// text.set_viewport_size(1280.0, 720.0);
+///
+/// enable_blend();
+/// set_blend_func(gl::SRC_ALPHA, gl::ONE_MINUS_SRC_ALPHA);
+/// text.draw_text("Score: 100", 16.0, 16.0, 1.0, Color::WHITE);
+/// ```
+pub struct TextRenderer {
+    program: ShaderProgram,
+    atlas: Texture2D,
+    glyphs: HashMap<char, Glyph>,
+    ascent: f32,
+    line_height: f32,
+    vao: Vao,
+    vbo: BufferObject,
+    vertices: Vec<[f32; 4]>,
+}
+
+impl TextRenderer {
+    /// Parse a TrueType or OpenType font and rasterize its glyphs at
+    /// `pixel_size`. Drawing at a `scale` far from 1.0 looks blurry, so pick
+    /// the size the text is usually shown at.
+    pub fn new(font_data: &[u8], pixel_size: f32) -> Result<TextRenderer, FontError> {
+        let font = fontdue::Font::from_bytes(font_data, fontdue::FontSettings::default())
+            .map_err(FontError::Parse)?;
+
+        let (ascent, line_height) = match font.horizontal_line_metrics(pixel_size) {
+            Some(line) => (line.ascent, line.new_line_size),
+            None => (pixel_size, pixel_size),
+        };
+
+        let bitmaps: Vec<(char, fontdue::Metrics, Vec<u8>)> = (' '..='~')
+            .map(|character| {
+                let (metrics, bitmap) = font.rasterize(character, pixel_size);
+                (character, metrics, bitmap)
+            })
+            .collect();
+
+        // Place the glyphs left to right in rows as tall as their tallest glyph.
+        let mut positions = Vec::with_capacity(bitmaps.len());
+        let (mut x, mut y, mut row_height) = (GLYPH_PADDING, GLYPH_PADDING, 0);
+        for (_, metrics, _) in &bitmaps {
+            if x + metrics.width + GLYPH_PADDING > ATLAS_WIDTH {
+                x = GLYPH_PADDING;
+                y += row_height + GLYPH_PADDING;
+                row_height = 0;
+            }
+            positions.push((x, y));
+            x += metrics.width + GLYPH_PADDING;
+            row_height = row_height.max(metrics.height);
+        }
+        let atlas_height = y + row_height + GLYPH_PADDING;
+
+        let mut pixels = vec![0u8; ATLAS_WIDTH * atlas_height];
+        let mut glyphs = HashMap::with_capacity(bitmaps.len());
+        for ((character, metrics, bitmap), (x, y)) in bitmaps.into_iter().zip(positions) {
+            for row in 0..metrics.height {
+                let start = (y + row) * ATLAS_WIDTH + x;
+                pixels[start..start + metrics.width]
+                    .copy_from_slice(&bitmap[row * metrics.width..(row + 1) * metrics.width]);
+            }
+
+            let uv = [
+                x as f32 / ATLAS_WIDTH as f32,
+                y as f32 / atlas_height as f32,
+                (x + metrics.width) as f32 / ATLAS_WIDTH as f32,
+                (y + metrics.height) as f32 / atlas_height as f32,
+            ];
+            glyphs.insert(character, Glyph { metrics, uv });
+        }
+
+        // Rows are stored top to bottom, so UV v = 0 is the top of the atlas.
+        let atlas = Texture2D::new();
+        atlas.bind();
+        atlas.load_from_r8(ATLAS_WIDTH as i32, atlas_height as i32, &pixels);
+        atlas.set_wrap_mode(gl::CLAMP_TO_EDGE, gl::CLAMP_TO_EDGE);
+        atlas.set_filter(gl::LINEAR, gl::LINEAR);
+        atlas.unbind();
+
+        let vao = Vao::new();
+        vao.bind();
+        let vbo = BufferObject::new(BufferTarget::Array, BufferUsage::DynamicDraw);
+        vbo.bind();
+        VertexLayout::new().push_f32(2).push_f32(2).apply();
+        vao.unbind();
+
+        let program = ShaderProgram::from_source(VERTEX_SHADER, FRAGMENT_SHADER)?;
+        program.set_uniform_i32("atlas", 0);
+
+        let mut renderer = TextRenderer {
+            program,
+            atlas,
+            glyphs,
+            ascent,
+            line_height,
+            vao,
+            vbo,
+            vertices: Vec::new(),
+        };
+        renderer.set_viewport_size(1.0, 1.0);
+
+        Ok(renderer)
+    }
+
+    /// Set the size of the viewport in pixels. Call it whenever the window is
+    /// resized.
+    pub fn set_viewport_size(&mut self, width: f32, height: f32) {
+        let camera = Camera::orthographic(0.0, width, height, 0.0, -1.0, 1.0);
+        self.program
+            .set_matrix4fv_uniform("projection", &camera.projection());
+    }
+
+    /// Draw `text` with the top-left corner of its first line at (`x`, `y`).
+    /// `\n` starts a new line; characters missing from the atlas are skipped.
+    /// Binds the text program and texture unit 0.
+    pub fn draw_text(&mut self, text: &str, x: f32, y: f32, scale: f32, color: Color) {
+        self.vertices.clear();
+
+        let mut pen_x = x;
+        let mut baseline = y + self.ascent * scale;
+        for character in text.chars() {
+            if character == '\n' {
+                pen_x = x;
+                baseline += self.line_height * scale;
+                continue;
+            }
+
+            let glyph = match self.glyphs.get(&character) {
+                Some(glyph) => glyph,
+                None => continue,
+            };
+
+            let metrics = glyph.metrics;
+            if metrics.width > 0 && metrics.height > 0 {
+                let left = pen_x + metrics.xmin as f32 * scale;
+                let top = baseline - (metrics.ymin as f32 + metrics.height as f32) * scale;
+                let right = left + metrics.width as f32 * scale;
+                let bottom = top + metrics.height as f32 * scale;
+                let [u0, v0, u1, v1] = glyph.uv;
+
+                self.vertices.extend_from_slice(&[
+                    [left, top, u0, v0],
+                    [right, top, u1, v0],
+                    [right, bottom, u1, v1],
+                    [right, bottom, u1, v1],
+                    [left, bottom, u0, v1],
+                    [left, top, u0, v0],
+                ]);
+            }
+            pen_x += metrics.advance_width * scale;
+        }
+
+        if self.vertices.is_empty() {
+            return;
+        }
+
+        self.program.bind();
+        self.program.set_uniform_color("color", &color);
+        self.atlas.bind_unit(0);
+
+        self.vao.bind();
+        self.vbo.bind();
+        self.vbo.store_data(&self.vertices);
+        draw_arrays(gl::TRIANGLES, 0, self.vertices.len() as i32);
+        self.vao.unbind();
+    }
+}
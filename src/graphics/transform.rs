@@ -0,0 +1,49 @@
+use cgmath::*;
+
+/// # Transform
+/// Position, rotation and scale of an object, combined into a model matrix.
+///
+/// ## Example
+/// ```ignore
+/// let mut transform = Transform::from_position(Vector3::new(0.0, 1.0, -5.0));
+/// transform.rotation = Quaternion::from_angle_y(Deg(45.0));
+///
+/// program.set_matrix4fv_uniform("model", &transform.matrix());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Transform {
+    pub position: Vector3<f32>,
+    pub rotation: Quaternion<f32>,
+    pub scale: Vector3<f32>,
+}
+
+impl Transform {
+    /// No translation, no rotation and a scale of one.
+    pub fn identity() -> Transform {
+        Transform {
+            position: Vector3::zero(),
+            rotation: Quaternion::one(),
+            scale: Vector3::new(1.0, 1.0, 1.0),
+        }
+    }
+
+    pub fn from_position(position: Vector3<f32>) -> Transform {
+        Transform {
+            position,
+            ..Transform::identity()
+        }
+    }
+
+    /// The model matrix, which scales first, then rotates, then translates.
+    pub fn matrix(&self) -> Matrix4<f32> {
+        Matrix4::from_translation(self.position)
+            * Matrix4::from(self.rotation)
+            * Matrix4::from_nonuniform_scale(self.scale.x, self.scale.y, self.scale.z)
+    }
+}
+
+impl Default for Transform {
+    fn default() -> Transform {
+        Transform::identity()
+    }
+}
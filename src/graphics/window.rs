@@ -6,7 +6,7 @@ use std::sync::mpsc::Receiver;
 /// An abstraction layer for creating a glfw window.
 ///
 /// ## Example
-/// ```
+/// ```ignore
 /// let mut window = Window::new(1280, 720, "Window Title");
 /// window.init_gl();
 ///
@@ -23,7 +23,35 @@ pub struct Window {
 impl Window {
     /// Create new window with settings
     pub fn new(width: u32, height: u32, title: &str) -> Window {
-        let glfw = glfw::init(glfw::FAIL_ON_ERRORS).unwrap();
+        Window::create(width, height, title, true)
+    }
+
+    /// Create an invisible window with a current GL context and loaded function
+    /// pointers. Meant for tests and tools that render offscreen, e.g. into a
+    /// `Framebuffer`. On a CI machine without a display, run under a virtual
+    /// one such as `xvfb-run cargo test`.
+    ///
+    /// ## Example
+    /// ```ignore
+    /// let _context = Window::new_hidden(64, 64);
+    ///
+    /// let layout = VertexLayout::new().push_f32(3).push_f32(2);
+    /// assert_eq!(layout.stride(), 20);
+    /// ```
+    pub fn new_hidden(width: u32, height: u32) -> Window {
+        let mut window = Window::create(width, height, "", false);
+        window.init_gl();
+        window
+    }
+
+    fn create(width: u32, height: u32, title: &str, visible: bool) -> Window {
+        let mut glfw = glfw::init(glfw::FAIL_ON_ERRORS).unwrap();
+
+        // A debug context gives `enable_debug_output` the most detailed messages.
+        glfw.window_hint(glfw::WindowHint::OpenGlDebugContext(cfg!(debug_assertions)));
+        glfw.window_hint(glfw::WindowHint::Visible(visible));
+        // Lets `enable_framebuffer_srgb` apply to the default framebuffer too.
+        glfw.window_hint(glfw::WindowHint::SRgbCapable(true));
 
         let (mut window, events) = glfw
             .create_window(width, height, title, glfw::WindowMode::Windowed)
@@ -43,6 +71,11 @@ impl Window {
     pub fn init_gl(&mut self) {
         self.window_handle.make_current();
         gl::load_with(|s| self.window_handle.get_proc_address(s) as *const _);
+        crate::graphics::gl_wrapper::forget_context();
+        #[cfg(feature = "bindless")]
+        crate::graphics::gl_wrapper::load_bindless_with(|s| {
+            self.window_handle.get_proc_address(s) as *const _
+        });
     }
 
     pub fn should_close(&self) -> bool {